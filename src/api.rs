@@ -1,24 +1,107 @@
-use crate::data::{AlternateLimits, BuildInfo, Category, GlobalTransferInfo, Log, Torrent};
+use crate::data::{
+    AlternateLimits, ApiVersion, BuildInfo, Category, GlobalTransferInfo, Hash, Log, MainData,
+    PeerLog, Preferences, Torrent,
+};
 use crate::error::{Error, Result};
-use crate::queries::{AddTorrent, LogRequest};
+use crate::queries::{AddTorrent, LogRequest, PreferencesPatch, TorrentFile, TorrentRequest};
+use futures::stream::{self, Stream};
 use log::*;
 use reqwest::{
-    header::{HeaderMap, SET_COOKIE},
-    Response,
+    header::{HeaderMap, CONTENT_TYPE, COOKIE, SET_COOKIE},
+    multipart::{Form, Part},
+    RequestBuilder, Response, ResponseBuilderExt, StatusCode,
 };
 use serde::{de::DeserializeOwned, Serialize};
-use std::collections::{HashMap, HashSet};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, RwLock};
+use std::time::{Duration, Instant};
 use url::Url;
 
+/// Interval between torrent list requests in `Api::add_torrent_and_wait`.
+const ADD_TORRENT_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Lower bound of the polling interval in `Api::tail_logs`.
+const TAIL_LOGS_MIN_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Upper bound of the retry delay in `Api::tail_logs`, unless the polling
+/// interval is longer.
+const TAIL_LOGS_MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// `torrents/pause` and `torrents/resume` were renamed to `stop` and `start`,
+/// and the `paused` and `resumed` filters to `stopped` and `running`.
+pub(crate) const STOP_START_VERSION: ApiVersion = ApiVersion::new(2, 11, 0);
+
+/// `proxy_type` is sent as a name instead of an integer since qbittorrent 4.6.
+const PROXY_TYPE_NAME_VERSION: ApiVersion = ApiVersion::new(2, 9, 2);
+
+/// First version with `/api/v2/app/buildInfo`.
+const BUILD_INFO_VERSION: ApiVersion = ApiVersion::new(2, 3, 0);
+
+/// Requests to this endpoint are never recorded, the form holds the password.
+const LOGIN_PATH: &str = "/api/v2/auth/login";
+
+/// Replaces the values of secret fields in recorded forms and responses.
+const REDACTED: &str = "<redacted>";
+
 /// Main handle and access point to working with qbittorrent.
 ///
 /// Full documentation on provided methods is available
 /// [here](https://github.com/qbittorrent/qBittorrent/wiki/WebUI-API-(qBittorrent-4.1))
-#[derive(Debug)]
+///
+/// The login credentials are kept so that an expired session can be renewed
+/// transparently, requests which fail with `403 Forbidden` are retried once
+/// after logging in again.
+///
+/// The WebUI API version is queried at login. Endpoints which were renamed are
+/// called by the name the server knows, and endpoints the server does not have
+/// fail with `Error::Unsupported` without sending a request.
 pub struct Api {
     pub(crate) url: Url,
-    pub(crate) headers: HeaderMap,
+    pub(crate) headers: RwLock<HeaderMap>,
     pub(crate) client: reqwest::Client,
+    login_form: Vec<(String, String)>,
+    version: ApiVersion,
+    recorder: Mutex<Option<Recorder>>,
+}
+
+/// Destination of record mode, see `Api::set_record_dir`.
+#[derive(Debug)]
+struct Recorder {
+    dir: PathBuf,
+    /// Number of the next recorded exchange.
+    next: u64,
+}
+
+impl Recorder {
+    /// Record to `dir`, numbering exchanges after those already in it.
+    fn new(dir: PathBuf) -> Self {
+        let next = std::fs::read_dir(&dir)
+            .into_iter()
+            .flatten()
+            .filter_map(|entry| {
+                let name = entry.ok()?.file_name();
+                let (number, _) = name.to_str()?.split_once('-')?;
+                number.parse::<u64>().ok()
+            })
+            .max()
+            .map_or(0, |n| n + 1);
+        Self { dir, next }
+    }
+}
+
+impl fmt::Debug for Api {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Api")
+            .field("url", &self.url)
+            .field("headers", &self.headers)
+            .field("client", &self.client)
+            .field("version", &self.version)
+            .field("recorder", &self.recorder)
+            .finish_non_exhaustive()
+    }
 }
 
 impl Api {
@@ -33,59 +116,247 @@ impl Api {
         let mut headers = HeaderMap::new();
         headers.insert("referer", url.as_str().parse()?);
 
+        let login_form = form
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+
         let mut api = Self {
             url,
-            headers,
+            headers: RwLock::new(headers),
             client,
+            login_form,
+            version: ApiVersion::default(),
+            recorder: Mutex::new(None),
         };
 
-        let response = api.post("/api/v2/auth/login", form).await?;
+        api.login().await?;
+        api.version = api.get_api_version().await?.parse()?;
+        debug!("{:?}", api);
+        Ok(api)
+    }
+
+    pub async fn auth(url: &str, username: &str, password: &str) -> Result<Self> {
+        let mut form = HashMap::new();
+        form.insert("username", username);
+        form.insert("password", password);
+        Self::new(url, &form).await
+    }
+
+    pub async fn local(url: &str) -> Result<Self> {
+        let form = HashMap::new();
+        Self::new(url, &form).await
+    }
+
+    /// Run the login flow with the stored credentials and replace the
+    /// session cookie. A banned client IP fails with `Error::Forbidden`.
+    async fn login(&self) -> Result<()> {
+        let mut headers = self.headers();
+        headers.remove(COOKIE);
+
+        let path = LOGIN_PATH;
+        let response = self
+            .send_once(path, headers, |r| r.form(&self.login_form))
+            .await?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            debug!("POST <- {:?} STATUS {} {:?}", path, status, body);
+            return Err(Error::from_status(path, status, body));
+        }
 
         for cookie in response.headers().get_all(SET_COOKIE) {
             let cookie = cookie.to_str()?;
             if cookie.starts_with("SID=") {
                 let sid_cookie = cookie.split(";").next().unwrap();
-                api.headers.insert("cookie", sid_cookie.parse()?);
-                debug!("{:?}", api);
-                return Ok(api);
+                let value = sid_cookie.parse()?;
+                self.headers.write().unwrap().insert(COOKIE, value);
+                return Ok(());
             }
         }
 
         Err(Error::MissingCookie)
     }
 
-    pub async fn auth(url: &str, username: &str, password: &str) -> Result<Self> {
-        let mut form = HashMap::new();
-        form.insert("username", username);
-        form.insert("password", password);
-        Self::new(url, &form).await
+    /// WebUI API version of the server, queried at login.
+    pub fn api_version(&self) -> ApiVersion {
+        self.version
     }
 
-    pub async fn local(url: &str) -> Result<Self> {
-        let form = HashMap::new();
-        Self::new(url, &form).await
+    /// Fail with `Error::Unsupported` unless the server has at least API
+    /// version `needs`.
+    pub(crate) fn require(&self, needs: ApiVersion) -> Result<()> {
+        if self.version < needs {
+            return Err(Error::Unsupported {
+                needs,
+                have: self.version,
+            });
+        }
+        Ok(())
+    }
+
+    /// Pick the endpoint by whether the server has API version `since`.
+    pub(crate) fn versioned<'a>(&self, since: ApiVersion, path: &'a str, old: &'a str) -> &'a str {
+        if self.version >= since {
+            path
+        } else {
+            old
+        }
     }
 
     //
     // Internal post request functions and utils.
     //
 
+    fn headers(&self) -> HeaderMap {
+        self.headers.read().unwrap().clone()
+    }
+
+    async fn send_once<B>(&self, path: &str, headers: HeaderMap, build: B) -> Result<Response>
+    where
+        B: Fn(RequestBuilder) -> RequestBuilder,
+    {
+        let mut url = self.url.clone();
+        url.set_path(path);
+        let request = build(self.client.post(url).headers(headers)).build()?;
+        debug!("POST -> {:?} {:?}", path, request);
+        let form = request
+            .body()
+            .and_then(|body| body.as_bytes())
+            .map(|body| String::from_utf8_lossy(body).into_owned());
+        let response = self.client.execute(request).await?;
+        debug!("POST <- {:?} {:?}", path, response);
+
+        if path == LOGIN_PATH {
+            return Ok(response);
+        }
+        let next = self.recorder.lock().unwrap().as_mut().map(|recorder| {
+            recorder.next += 1;
+            (recorder.dir.clone(), recorder.next - 1)
+        });
+        match next {
+            Some((dir, number)) => self.record(&dir, number, path, form, response).await,
+            None => Ok(response),
+        }
+    }
+
+    /// Record every request and response to `dir`, or stop recording if
+    /// `None`.
+    ///
+    /// Each exchange is written to its own pair of files, numbered in the
+    /// order the responses arrive: `0003-torrents_info.request` holds the
+    /// endpoint, the urlencoded form and the status as json, and
+    /// `0003-torrents_info.json` the response body, `.txt` for plain text
+    /// and `.bin` for anything else. Failed requests are recorded like successful ones. Numbering
+    /// continues after the exchanges already in `dir`.
+    ///
+    /// Bodies are written as received, unless they hold a secret: those are
+    /// parsed, redacted and written back pretty-printed. Logins are not
+    /// recorded, password and cookie fields are redacted wherever they are
+    /// nested, and multipart forms are recorded as `null`. Failures to record
+    /// are logged and do not affect the request.
+    pub fn set_record_dir(&self, dir: Option<PathBuf>) {
+        *self.recorder.lock().unwrap() = dir.map(Recorder::new);
+    }
+
+    async fn record(
+        &self,
+        dir: &Path,
+        number: u64,
+        path: &str,
+        form: Option<String>,
+        response: Response,
+    ) -> Result<Response> {
+        let status = response.status();
+        let version = response.version();
+        let headers = response.headers().clone();
+        let url = response.url().clone();
+        let body = response.bytes().await?;
+
+        let form = form.map(|form| redact_form(&form));
+        info!("POST <- {:?} {} {:?}", path, status.as_u16(), form);
+        let content_type = headers
+            .get(CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok());
+        let request = serde_json::json!({
+            "endpoint": path,
+            "form": form,
+            "status": status.as_u16(),
+            "content_type": content_type,
+        });
+        let extension = match content_type {
+            Some(value) if value.contains("json") => "json",
+            None => "txt",
+            Some(value) if value.starts_with("text/") => "txt",
+            Some(_) => "bin",
+        };
+        let name = format!(
+            "{:04}-{}",
+            number,
+            path.trim_start_matches("/api/v2/").replace('/', "_")
+        );
+        match Self::write_record(dir, &name, &request, extension, &body).await {
+            Ok(()) => debug!("POST <- {:?} recorded to {:?}", path, dir.join(name)),
+            Err(e) => warn!("POST <- {:?} not recorded: {}", path, e),
+        }
+
+        let mut replay = http::Response::builder()
+            .status(status)
+            .version(version)
+            .url(url)
+            .body(body)
+            .map_err(|_| Error::BadResponse)?;
+        *replay.headers_mut() = headers;
+        Ok(replay.into())
+    }
+
+    async fn write_record(
+        dir: &Path,
+        name: &str,
+        request: &serde_json::Value,
+        extension: &str,
+        body: &[u8],
+    ) -> Result<()> {
+        tokio::fs::create_dir_all(dir).await?;
+        let file = dir.join(format!("{}.request", name));
+        tokio::fs::write(&file, serde_json::to_vec_pretty(request)?).await?;
+        let file = dir.join(format!("{}.{}", name, extension));
+        let redacted = (extension == "json").then(|| redact_json(body)).flatten();
+        match redacted {
+            Some(value) => tokio::fs::write(&file, serde_json::to_vec_pretty(&value)?).await?,
+            None => tokio::fs::write(&file, body).await?,
+        }
+        Ok(())
+    }
+
+    /// Send a request, logging in again and retrying once if the session has
+    /// expired. Non-success responses are mapped to typed errors.
+    pub(crate) async fn send<B>(&self, path: &str, build: B) -> Result<Response>
+    where
+        B: Fn(RequestBuilder) -> RequestBuilder,
+    {
+        let mut response = self.send_once(path, self.headers(), &build).await?;
+        if response.status() == StatusCode::FORBIDDEN {
+            info!("POST <- {:?} forbidden, renewing session", path);
+            self.login().await?;
+            response = self.send_once(path, self.headers(), &build).await?;
+        }
+
+        let status = response.status();
+        if status.is_success() {
+            return Ok(response);
+        }
+        let body = response.text().await.unwrap_or_default();
+        debug!("POST <- {:?} STATUS {} {:?}", path, status, body);
+        Err(Error::from_status(path, status, body))
+    }
+
     pub(crate) async fn post<F: Serialize + ?Sized>(
         &self,
         path: &str,
         form: &F,
     ) -> Result<Response> {
-        let mut url = self.url.clone();
-        url.set_path(path);
-        let request = self
-            .client
-            .post(url)
-            .headers(self.headers.clone())
-            .form(form);
-        debug!("POST -> {:?} {:?}", path, request);
-        let response = request.send().await?;
-        debug!("POST <- {:?} {:?}", path, response);
-        Ok(response)
+        self.send(path, |r| r.form(form)).await
     }
 
     pub(crate) async fn post_status<F: Serialize + ?Sized>(
@@ -93,11 +364,8 @@ impl Api {
         path: &str,
         form: &F,
     ) -> Result<()> {
-        let response = self.post(path, form).await?;
-        match response.error_for_status() {
-            Ok(_) => Ok(()),
-            Err(e) => Err(Error::from(e)),
-        }
+        self.post(path, form).await?;
+        Ok(())
     }
 
     pub(crate) async fn post_decode<F: Serialize + ?Sized, T: DeserializeOwned>(
@@ -112,6 +380,17 @@ impl Api {
         Ok(ret)
     }
 
+    pub(crate) async fn post_bytes<F: Serialize + ?Sized>(
+        &self,
+        path: &str,
+        form: &F,
+    ) -> Result<Vec<u8>> {
+        let response = self.post(path, form).await?;
+        let data = response.bytes().await?;
+        debug!("POST <- {:?} BYTES {}", path, data.len());
+        Ok(data.to_vec())
+    }
+
     pub(crate) async fn post_text<F: Serialize + ?Sized>(
         &self,
         path: &str,
@@ -136,6 +415,7 @@ impl Api {
     }
 
     pub async fn get_build_info(&self) -> Result<BuildInfo> {
+        self.require(BUILD_INFO_VERSION)?;
         self.post_decode("/api/v2/app/buildInfo", &()).await
     }
 
@@ -143,10 +423,75 @@ impl Api {
         self.post_text("/api/v2/app/defaultSavePath", &()).await
     }
 
+    pub async fn get_preferences(&self) -> Result<Preferences> {
+        self.post_decode("/api/v2/app/preferences", &()).await
+    }
+
+    /// Apply the fields set in `patch`, all other preferences are unchanged.
+    pub async fn set_preferences(&self, patch: &PreferencesPatch) -> Result<()> {
+        let mut form = HashMap::new();
+        form.insert("json", Self::preferences_json(patch, self.version)?);
+        self.post_status("/api/v2/app/setPreferences", &form).await
+    }
+
+    /// `patch` as json, with the proxy type as an integer for servers older
+    /// than `PROXY_TYPE_NAME_VERSION`.
+    fn preferences_json(patch: &PreferencesPatch, version: ApiVersion) -> Result<String> {
+        let mut json = serde_json::to_value(patch)?;
+        if let Some(proxy_type) = patch.proxy_type {
+            if version < PROXY_TYPE_NAME_VERSION {
+                let auth = patch.proxy_auth_enabled == Some(true);
+                json["proxy_type"] = proxy_type.code(auth).into();
+            }
+        }
+        Ok(json.to_string())
+    }
+
     pub async fn get_main_logs(&self, logs: &LogRequest) -> Result<Vec<Log>> {
         self.post_decode("/api/v2/log/main", &logs).await
     }
 
+    /// Follow the main log, polling every `interval` for entries newer than
+    /// the highest id seen so far. Intervals shorter than 100ms are raised to
+    /// 100ms.
+    ///
+    /// Failed requests are yielded as errors and retried with an exponential
+    /// backoff, the stream itself never ends. Drop the stream to stop tailing.
+    pub fn tail_logs(
+        &self,
+        request: LogRequest,
+        interval: Duration,
+    ) -> impl Stream<Item = Result<Log>> + '_ {
+        let tail = Tail::new(request, interval);
+        stream::unfold(tail, move |mut tail| async move {
+            loop {
+                if let Some(log) = tail.pending.pop_front() {
+                    return Some((Ok(log), tail));
+                }
+                if let Some(delay) = tail.delay {
+                    tokio::time::sleep(delay).await;
+                }
+                match self.get_main_logs(&tail.request).await {
+                    Ok(logs) => tail.received(logs),
+                    Err(e) => {
+                        tail.failed();
+                        warn!("tail_logs: {}, retrying in {:?}", e, tail.delay);
+                        return Some((Err(e), tail));
+                    }
+                }
+            }
+        })
+    }
+
+    /// Peer log entries after `last_known_id`, or all of them if `None`.
+    pub async fn get_peer_logs(&self, last_known_id: Option<u64>) -> Result<Vec<PeerLog>> {
+        let mut form = HashMap::new();
+        if let Some(id) = last_known_id {
+            form.insert("last_known_id", id);
+        }
+        self.post_decode("/api/v2/log/peers", &form).await
+    }
+
     pub async fn shutdown(&self) -> Result<()> {
         self.post_status("/api/v2/app/shutdown", &()).await
     }
@@ -175,6 +520,67 @@ impl Api {
             .await
     }
 
+    /// Switch the alternative speed limits to `state`, does nothing if they
+    /// are already in that state.
+    pub async fn set_alt_speed_limits(&self, state: AlternateLimits) -> Result<()> {
+        if self.get_alt_speed_limits_state().await? != state {
+            self.toggle_alt_speed_limits().await?;
+        }
+        Ok(())
+    }
+
+    /// Global download limit (bytes/s), 0 if unlimited.
+    pub async fn get_download_limit(&self) -> Result<i64> {
+        let text = self
+            .post_text("/api/v2/transfer/downloadLimit", &())
+            .await?;
+        text.trim().parse().map_err(|_| Error::BadResponse)
+    }
+
+    /// Set the global download limit (bytes/s), 0 for unlimited.
+    pub async fn set_download_limit(&self, limit: i64) -> Result<()> {
+        let mut form = HashMap::new();
+        form.insert("limit", limit);
+        self.post_status("/api/v2/transfer/setDownloadLimit", &form)
+            .await
+    }
+
+    /// Global upload limit (bytes/s), 0 if unlimited.
+    pub async fn get_upload_limit(&self) -> Result<i64> {
+        let text = self.post_text("/api/v2/transfer/uploadLimit", &()).await?;
+        text.trim().parse().map_err(|_| Error::BadResponse)
+    }
+
+    /// Set the global upload limit (bytes/s), 0 for unlimited.
+    pub async fn set_upload_limit(&self, limit: i64) -> Result<()> {
+        let mut form = HashMap::new();
+        form.insert("limit", limit);
+        self.post_status("/api/v2/transfer/setUploadLimit", &form)
+            .await
+    }
+
+    /// Ban `peers` permanently, they are sent as `ip:port` separated by `|`.
+    /// IPv6 addresses are written in brackets, e.g. `[2001:db8::1]:6881`, as
+    /// `SocketAddr` formats them.
+    pub async fn ban_peers(&self, peers: &[SocketAddr]) -> Result<()> {
+        let peers: Vec<String> = peers.iter().map(|p| p.to_string()).collect();
+        let mut form = HashMap::new();
+        form.insert("peers", peers.join("|"));
+        self.post_status("/api/v2/transfer/banPeers", &form).await
+    }
+
+    //
+    // Sync
+    //
+
+    /// Changes to the main data since response id `rid`, use `0` to request
+    /// a full update.
+    pub async fn sync_maindata(&self, rid: u64) -> Result<MainData> {
+        let mut form = HashMap::new();
+        form.insert("rid", rid);
+        self.post_decode("/api/v2/sync/maindata", &form).await
+    }
+
     //
     // Torrents
     //
@@ -183,8 +589,97 @@ impl Api {
         self.post_decode("/api/v2/torrents/info", &()).await
     }
 
-    pub async fn add_torrent(&self, torrent: &AddTorrent) -> Result<()> {
-        self.post_status("/api/v2/torrents/add", &torrent).await
+    pub async fn get_torrents_filtered(&self, request: &TorrentRequest) -> Result<Vec<Torrent>> {
+        let mut request = request.clone();
+        let stop_start = self.version >= STOP_START_VERSION;
+        request.filter = request.filter.map(|f| f.renamed(stop_start));
+        self.post_decode("/api/v2/torrents/info", &request).await
+    }
+
+    /// Add torrents from urls and .torrent files. The request is sent as
+    /// `multipart/form-data` when it contains .torrent files.
+    ///
+    /// Returns the hashes of the added magnet links and .torrent files, the
+    /// hashes of torrents downloaded from other urls are not known in advance.
+    /// .torrent files this crate cannot parse are still sent to the server,
+    /// but their hashes are left out.
+    pub async fn add_torrent(&self, torrent: &AddTorrent) -> Result<Vec<Hash>> {
+        let hashes = torrent.hashes()?;
+
+        let path = "/api/v2/torrents/add";
+        let paused = self.versioned(STOP_START_VERSION, "stopped", "paused");
+        let encoded = serde_urlencoded::to_string(torrent)?;
+        let fields: Vec<(String, String)> = url::form_urlencoded::parse(encoded.as_bytes())
+            .into_owned()
+            .map(|(key, value)| match key.as_str() {
+                "paused" => (paused.to_string(), value),
+                _ => (key, value),
+            })
+            .collect();
+        let text = if torrent.torrents.is_empty() {
+            self.post_text(path, &fields).await?
+        } else {
+            let response = self
+                .send(path, |r| {
+                    let mut form = Form::new();
+                    for (key, value) in &fields {
+                        form = form.text(key.clone(), value.clone());
+                    }
+                    for file in &torrent.torrents {
+                        let part = Part::bytes(file.data.clone()).file_name(file.filename.clone());
+                        form = form.part("torrents", part);
+                    }
+                    r.multipart(form)
+                })
+                .await?;
+            response.text().await?
+        };
+
+        if text.trim() == "Fails." {
+            return Err(Error::AddTorrentFailed);
+        }
+        Ok(hashes)
+    }
+
+    /// Add torrents like `add_torrent`, then wait up to `timeout` until all of
+    /// the returned hashes appear in the torrent list.
+    pub async fn add_torrent_and_wait(
+        &self,
+        torrent: &AddTorrent,
+        timeout: Duration,
+    ) -> Result<Vec<Hash>> {
+        let hashes = self.add_torrent(torrent).await?;
+        if hashes.is_empty() {
+            return Ok(hashes);
+        }
+
+        let mut request = TorrentRequest::default();
+        request.hashes = hashes.clone();
+        let deadline = Instant::now() + timeout;
+        loop {
+            let torrents = self.get_torrents_filtered(&request).await?;
+            let found: HashSet<&str> = torrents.iter().map(|t| t.hash.as_str()).collect();
+            if hashes.iter().all(|h| found.contains(h.as_str())) {
+                return Ok(hashes);
+            }
+            if Instant::now() >= deadline {
+                return Err(Error::Timeout);
+            }
+            tokio::time::sleep(ADD_TORRENT_POLL_INTERVAL).await;
+        }
+    }
+
+    /// Add the .torrent file at `path` with the default options.
+    ///
+    /// The file is parsed first, a file which is not a valid torrent fails
+    /// with `Error::InvalidTorrent` without being uploaded.
+    pub async fn add_torrent_file(&self, path: impl AsRef<Path>) -> Result<Hash> {
+        let file = TorrentFile::read(path).await?;
+        let hash = file.metainfo()?.hash();
+        let mut torrent = AddTorrent::default();
+        torrent.torrents.push(file);
+        self.add_torrent(&torrent).await?;
+        Ok(hash)
     }
 
     //
@@ -241,23 +736,225 @@ impl Api {
         ret
     }
 
-    pub async fn create_tags<T>(&self, tags: T) -> Result<HashSet<String>>
+    pub async fn create_tags<T>(&self, tags: T) -> Result<()>
     where
         T: IntoIterator,
         T::Item: AsRef<str>,
     {
         let mut form: HashMap<&str, String> = HashMap::new();
         form.insert("tags", Self::join_tags(tags));
-        self.post_decode("/api/v2/torrents/createTags", &form).await
+        self.post_status("/api/v2/torrents/createTags", &form).await
     }
 
-    pub async fn delete_tags<T>(&self, tags: T) -> Result<HashSet<String>>
+    pub async fn delete_tags<T>(&self, tags: T) -> Result<()>
     where
         T: IntoIterator,
         T::Item: AsRef<str>,
     {
         let mut form: HashMap<&str, String> = HashMap::new();
         form.insert("tags", Self::join_tags(tags));
-        self.post_decode("/api/v2/torrents/deleteTags", &form).await
+        self.post_status("/api/v2/torrents/deleteTags", &form).await
+    }
+}
+
+/// Whether a form field or preference holds a secret which is not recorded.
+fn is_secret(key: &str) -> bool {
+    key.contains("password") || key == "cookie"
+}
+
+/// The json value in `data` with its secret fields redacted, `None` if it has
+/// none set.
+fn redact_json(data: &[u8]) -> Option<serde_json::Value> {
+    let mut value: serde_json::Value = serde_json::from_slice(data).ok()?;
+    redact_value(&mut value).then_some(value)
+}
+
+/// Redact the secret fields of `value` and of every object nested in it,
+/// returning whether any were set.
+fn redact_value(value: &mut serde_json::Value) -> bool {
+    let mut redacted = false;
+    match value {
+        serde_json::Value::Object(object) => {
+            for (key, value) in object {
+                if is_secret(key) && value.as_str().is_some_and(|s| !s.is_empty()) {
+                    *value = REDACTED.into();
+                    redacted = true;
+                } else {
+                    redacted |= redact_value(value);
+                }
+            }
+        }
+        serde_json::Value::Array(values) => {
+            for value in values {
+                redacted |= redact_value(value);
+            }
+        }
+        _ => {}
+    }
+    redacted
+}
+
+/// Redact the secret fields of an urlencoded form, including the fields of
+/// the json encoded preferences sent to `app/setPreferences`.
+fn redact_form(form: &str) -> String {
+    let pairs = url::form_urlencoded::parse(form.as_bytes()).map(|(key, value)| {
+        let value = if is_secret(&key) {
+            REDACTED.into()
+        } else if key == "json" {
+            redact_json(value.as_bytes())
+                .map_or_else(|| value.into_owned(), |json| json.to_string())
+        } else {
+            value.into_owned()
+        };
+        (key, value)
+    });
+    url::form_urlencoded::Serializer::new(String::new())
+        .extend_pairs(pairs)
+        .finish()
+}
+
+/// State of an `Api::tail_logs` stream between polls.
+struct Tail {
+    request: LogRequest,
+    interval: Duration,
+    pending: VecDeque<Log>,
+    last_id: Option<u64>,
+    /// Sleep before the next poll, `None` polls immediately.
+    delay: Option<Duration>,
+}
+
+impl Tail {
+    fn new(request: LogRequest, interval: Duration) -> Self {
+        Self {
+            request,
+            interval: interval.max(TAIL_LOGS_MIN_INTERVAL),
+            pending: VecDeque::new(),
+            last_id: None,
+            delay: None,
+        }
+    }
+
+    /// Queue the entries not seen yet and poll again after the interval.
+    fn received(&mut self, logs: Vec<Log>) {
+        for log in logs {
+            if self.last_id.is_some_and(|id| *log.id() <= id) {
+                continue;
+            }
+            self.last_id = Some(*log.id());
+            self.request.last_known_id = Some(*log.id());
+            self.pending.push_back(log);
+        }
+        self.delay = Some(self.interval);
+    }
+
+    /// Double the delay, never retrying sooner than the interval.
+    fn failed(&mut self) {
+        let backoff = match self.delay {
+            Some(delay) if delay > self.interval => delay * 2,
+            _ => self.interval * 2,
+        };
+        let max = self.interval.max(TAIL_LOGS_MAX_BACKOFF);
+        self.delay = Some(backoff.min(max));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::ProxyType;
+    use crate::queries::PreferencesPatchBuilder;
+
+    fn logs(ids: &[u64]) -> Vec<Log> {
+        ids.iter()
+            .map(|id| {
+                let log = serde_json::json!({"id": id, "message": "", "timestamp": 0, "type": 1});
+                serde_json::from_value(log).unwrap()
+            })
+            .collect()
+    }
+
+    fn pending(tail: &mut Tail) -> Vec<u64> {
+        tail.pending.drain(..).map(|log| *log.id()).collect()
+    }
+
+    #[test]
+    fn tail_deduplicates() {
+        let mut tail = Tail::new(LogRequest::default(), Duration::from_secs(1));
+        assert_eq!(tail.request.last_known_id, None);
+
+        tail.received(logs(&[0, 1, 2]));
+        assert_eq!(pending(&mut tail), [0, 1, 2]);
+        assert_eq!(tail.request.last_known_id, Some(2));
+
+        // Servers ignoring last_known_id repeat old entries.
+        tail.received(logs(&[1, 2, 3, 4]));
+        assert_eq!(pending(&mut tail), [3, 4]);
+        tail.received(logs(&[]));
+        assert!(tail.pending.is_empty());
+        assert_eq!(tail.request.last_known_id, Some(4));
+    }
+
+    #[test]
+    fn tail_backoff() {
+        let secs = Duration::from_secs;
+        let mut tail = Tail::new(LogRequest::default(), secs(5));
+        let mut delays = Vec::new();
+        for _ in 0..6 {
+            tail.failed();
+            delays.push(tail.delay.unwrap());
+        }
+        assert_eq!(
+            delays,
+            [secs(10), secs(20), secs(40), secs(60), secs(60), secs(60)]
+        );
+        tail.received(Vec::new());
+        assert_eq!(tail.delay, Some(secs(5)));
+
+        // The backoff never polls sooner than the interval.
+        let mut tail = Tail::new(LogRequest::default(), secs(120));
+        tail.failed();
+        assert_eq!(tail.delay, Some(secs(120)));
+
+        let mut tail = Tail::new(LogRequest::default(), Duration::ZERO);
+        tail.received(Vec::new());
+        assert_eq!(tail.delay, Some(TAIL_LOGS_MIN_INTERVAL));
+        tail.failed();
+        assert_eq!(tail.delay, Some(TAIL_LOGS_MIN_INTERVAL * 2));
+    }
+
+    #[test]
+    fn preferences_proxy_type() {
+        let patch = PreferencesPatchBuilder::default()
+            .proxy_type(ProxyType::Socks5)
+            .proxy_auth_enabled(true)
+            .build()
+            .unwrap();
+        let json = |version| {
+            let json = Api::preferences_json(&patch, version).unwrap();
+            serde_json::from_str::<serde_json::Value>(&json).unwrap()["proxy_type"].clone()
+        };
+        assert_eq!(json(ApiVersion::new(2, 11, 2)), "SOCKS5");
+        assert_eq!(json(ApiVersion::new(2, 9, 2)), "SOCKS5");
+        assert_eq!(json(ApiVersion::new(2, 8, 5)), 4);
+
+        let patch = PreferencesPatchBuilder::default().build().unwrap();
+        let json = Api::preferences_json(&patch, ApiVersion::new(2, 8, 5)).unwrap();
+        assert_eq!(json, "{}");
+    }
+
+    #[test]
+    fn redact_nested() {
+        let data =
+            br#"{"feeds": [{"url": "a", "password": "hunter2"}], "proxy": {"proxy_password": ""}}"#;
+        let value = redact_json(data).unwrap();
+        assert_eq!(value["feeds"][0]["password"], REDACTED);
+        assert_eq!(value["feeds"][0]["url"], "a");
+        assert_eq!(value["proxy"]["proxy_password"], "");
+        assert!(redact_json(br#"[{"name": "a"}]"#).is_none());
+
+        let form =
+            redact_form("hashes=all&json=%7B%22rss%22%3A%7B%22mail_password%22%3A%22x%22%7D%7D");
+        assert!(form.starts_with("hashes=all&json="));
+        assert!(!form.contains("%22x%22"));
     }
 }
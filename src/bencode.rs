@@ -0,0 +1,213 @@
+//! Minimal bencode decoder for .torrent files.
+//!
+//! Dictionaries keep a reference to their raw encoding, the infohash of a
+//! torrent is the digest of the raw `info` dictionary.
+
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone)]
+pub(crate) enum Value<'a> {
+    Int(i64),
+    Bytes(&'a [u8]),
+    List(Vec<Value<'a>>),
+    Dict(Dict<'a>),
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct Dict<'a> {
+    raw: &'a [u8],
+    entries: BTreeMap<&'a [u8], Value<'a>>,
+}
+
+impl<'a> Dict<'a> {
+    /// The dictionary exactly as it was encoded.
+    pub(crate) fn raw(&self) -> &'a [u8] {
+        self.raw
+    }
+
+    pub(crate) fn get(&self, key: &str) -> Option<&Value<'a>> {
+        self.entries.get(key.as_bytes())
+    }
+
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (&'a [u8], &Value<'a>)> {
+        self.entries.iter().map(|(k, v)| (*k, v))
+    }
+}
+
+impl<'a> Value<'a> {
+    pub(crate) fn as_int(&self) -> Option<i64> {
+        match self {
+            Value::Int(i) => Some(*i),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_bytes(&self) -> Option<&'a [u8]> {
+        match self {
+            Value::Bytes(b) => Some(b),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_list(&self) -> Option<&[Value<'a>]> {
+        match self {
+            Value::List(l) => Some(l),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_dict(&self) -> Option<&Dict<'a>> {
+        match self {
+            Value::Dict(d) => Some(d),
+            _ => None,
+        }
+    }
+}
+
+/// Decode a single bencoded value, trailing data is an error.
+pub(crate) fn decode(data: &[u8]) -> Result<Value<'_>, String> {
+    let mut decoder = Decoder { data, pos: 0 };
+    let value = decoder.value(0)?;
+    if decoder.pos != data.len() {
+        return Err(format!("trailing data at offset {}", decoder.pos));
+    }
+    Ok(value)
+}
+
+/// Nesting limit, guards against stack exhaustion on malicious input.
+const MAX_DEPTH: usize = 64;
+
+struct Decoder<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Decoder<'a> {
+    fn peek(&self) -> Result<u8, String> {
+        self.data
+            .get(self.pos)
+            .copied()
+            .ok_or_else(|| "unexpected end of data".to_string())
+    }
+
+    fn value(&mut self, depth: usize) -> Result<Value<'a>, String> {
+        if depth > MAX_DEPTH {
+            return Err("nesting too deep".into());
+        }
+        match self.peek()? {
+            b'i' => {
+                self.pos += 1;
+                let int = self.until(b'e')?;
+                let int = std::str::from_utf8(int).map_err(|e| e.to_string())?;
+                let int = int
+                    .parse()
+                    .map_err(|_| format!("invalid integer {:?}", int))?;
+                Ok(Value::Int(int))
+            }
+            b'l' => {
+                self.pos += 1;
+                let mut list = Vec::new();
+                while self.peek()? != b'e' {
+                    list.push(self.value(depth + 1)?);
+                }
+                self.pos += 1;
+                Ok(Value::List(list))
+            }
+            b'd' => {
+                let start = self.pos;
+                self.pos += 1;
+                let mut entries = BTreeMap::new();
+                while self.peek()? != b'e' {
+                    let key = self.bytes()?;
+                    let value = self.value(depth + 1)?;
+                    entries.insert(key, value);
+                }
+                self.pos += 1;
+                Ok(Value::Dict(Dict {
+                    raw: &self.data[start..self.pos],
+                    entries,
+                }))
+            }
+            b'0'..=b'9' => Ok(Value::Bytes(self.bytes()?)),
+            c => Err(format!(
+                "unexpected byte {:?} at offset {}",
+                c as char, self.pos
+            )),
+        }
+    }
+
+    fn bytes(&mut self) -> Result<&'a [u8], String> {
+        let len = self.until(b':')?;
+        let len: usize = std::str::from_utf8(len)
+            .ok()
+            .and_then(|len| len.parse().ok())
+            .ok_or_else(|| "invalid string length".to_string())?;
+        let end = self
+            .pos
+            .checked_add(len)
+            .filter(|end| *end <= self.data.len())
+            .ok_or_else(|| "string length exceeds data".to_string())?;
+        let bytes = &self.data[self.pos..end];
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    /// Bytes up to, and skipping over, the `end` delimiter.
+    fn until(&mut self, end: u8) -> Result<&'a [u8], String> {
+        let rest = &self.data[self.pos..];
+        let len = rest
+            .iter()
+            .position(|b| *b == end)
+            .ok_or_else(|| "unexpected end of data".to_string())?;
+        self.pos += len + 1;
+        Ok(&rest[..len])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_values() {
+        let data = b"d4:listli-3ei0e3:abce6:nestedd1:x0:e3:numi42ee";
+        let value = decode(data).unwrap();
+        let dict = value.as_dict().unwrap();
+        assert_eq!(dict.raw(), data);
+        assert_eq!(dict.get("num").and_then(|v| v.as_int()), Some(42));
+
+        let list = dict.get("list").and_then(|v| v.as_list()).unwrap();
+        assert_eq!(list[0].as_int(), Some(-3));
+        assert_eq!(list[1].as_int(), Some(0));
+        assert_eq!(list[2].as_bytes(), Some(&b"abc"[..]));
+
+        let nested = dict.get("nested").and_then(|v| v.as_dict()).unwrap();
+        assert_eq!(nested.raw(), b"d1:x0:e");
+        assert_eq!(nested.get("x").and_then(|v| v.as_bytes()), Some(&b""[..]));
+
+        let keys: Vec<&[u8]> = dict.iter().map(|(key, _)| key).collect();
+        assert_eq!(keys, [&b"list"[..], b"nested", b"num"]);
+    }
+
+    #[test]
+    fn decode_malformed() {
+        let deep = "l".repeat(MAX_DEPTH + 2) + &"e".repeat(MAX_DEPTH + 2);
+        let malformed: [&[u8]; 12] = [
+            b"",
+            b"i12",
+            b"i1x2e",
+            b"ie",
+            b"5:abc",
+            b"-1:a",
+            b"l",
+            b"d1:ae",
+            b"di1ei2ee",
+            b"x",
+            b"i1ei2e",
+            deep.as_bytes(),
+        ];
+        for data in malformed {
+            assert!(decode(data).is_err(), "{:?}", String::from_utf8_lossy(data));
+        }
+    }
+}
@@ -1,31 +1,50 @@
 //! Structs returned by api queries
 
+use crate::magnet::Magnet;
 use derive_getters::Getters;
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::ops::Range;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
-/// Overall metadata about this qbit client
+/// A single response from `/api/v2/sync/maindata`.
+///
+/// Unless `full_update` is set, the objects only contain the fields which
+/// changed since the requested `rid`. Use [`crate::sync::SyncState`] to fold
+/// these partial updates into a complete local copy.
 #[derive(Debug, Deserialize, Getters)]
 pub struct MainData {
-    rid: u64,
-    full_update: bool,
-    torrents: Torrent,
-    torrents_removed: Vec<String>,
-    categories: Category,
-    categories_removed: Vec<String>,
-    tags: Vec<String>,
-    tags_removed: Vec<String>,
-    queueing: bool,
-    server_state: ServerState,
+    pub(crate) rid: u64,
+    #[serde(default)]
+    pub(crate) full_update: bool,
+    #[serde(default)]
+    pub(crate) torrents: HashMap<String, Map<String, Value>>,
+    #[serde(default)]
+    pub(crate) torrents_removed: Vec<String>,
+    #[serde(default)]
+    pub(crate) categories: HashMap<String, Map<String, Value>>,
+    #[serde(default)]
+    pub(crate) categories_removed: Vec<String>,
+    #[serde(default)]
+    pub(crate) tags: Vec<String>,
+    #[serde(default)]
+    pub(crate) tags_removed: Vec<String>,
+    #[serde(default)]
+    pub(crate) server_state: Map<String, Value>,
 }
 
 #[derive(Debug, Deserialize, Getters, Clone)]
 pub struct Torrent {
-    added_on: u32,
-    amount_left: u64,
+    /// Seconds since epoch
+    added_on: i64,
+    amount_left: i64,
     auto_tmm: bool,
     category: String,
     completed: i64,
-    completion_on: i32,
+    /// Seconds since epoch, `-1` or `4294967295` depending on the version if
+    /// the torrent has not completed
+    completion_on: i64,
     dl_limit: i64,
     dlspeed: i64,
     downloaded: i64,
@@ -64,13 +83,19 @@ pub struct Torrent {
     upspeed: i64,
 }
 
+impl Torrent {
+    /// Parsed `magnet_uri`.
+    pub fn magnet(&self) -> crate::error::Result<Magnet> {
+        self.magnet_uri.parse()
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, Getters)]
 pub struct Tracker {
     url: String,
     #[getter(skip)]
     status: i32,
-    // TODO: fix this since some people do things non standard with "/" here
-    // tier: u32,
+    tier: Tier,
     num_peers: i32,
     // TODO: documentation says these will be returned but the fields do not appear
     // num_seeds: i32,
@@ -80,6 +105,11 @@ pub struct Tracker {
 }
 
 impl Tracker {
+    /// Whether this is the DHT, PeX or LSD pseudo-entry rather than a tracker.
+    pub fn is_pseudo(&self) -> bool {
+        self.tier == Tier::Pseudo
+    }
+
     pub fn status(&self) -> TrackerStatus {
         match self.status {
             0 => TrackerStatus::TrackerDisabled,
@@ -92,6 +122,41 @@ impl Tracker {
     }
 }
 
+/// Tier of a tracker.
+///
+/// The DHT, PeX and LSD pseudo-entries have no tier, depending on the version
+/// it is reported as `-1`, an empty string or some other non-numeric value.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Tier {
+    Index(u32),
+    Pseudo,
+}
+
+impl Serialize for Tier {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Tier::Index(i) => serializer.serialize_u32(*i),
+            Tier::Pseudo => serializer.serialize_i32(-1),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Tier {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let tier = match Value::deserialize(deserializer)? {
+            Value::Number(n) => n.as_u64().and_then(|n| u32::try_from(n).ok()),
+            Value::String(s) => s.trim().parse().ok(),
+            _ => None,
+        };
+        Ok(tier.map(Tier::Index).unwrap_or(Tier::Pseudo))
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Getters)]
+pub struct WebSeed {
+    url: String,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum TrackerStatus {
     TrackerDisabled,
@@ -105,7 +170,9 @@ pub enum TrackerStatus {
 #[derive(Clone, Debug, Deserialize, Serialize, Getters)]
 pub struct TorrentProperties {
     save_path: String,
-    creation_date: u32,
+    /// Seconds since epoch, `-1` or `4294967295` depending on the version if
+    /// unknown
+    creation_date: i64,
     piece_size: i64,
     comment: String,
     total_wasted: i64,
@@ -129,12 +196,14 @@ pub struct TorrentProperties {
     last_seen: i64,
     peers: i64,
     peers_total: i64,
-    pieces_have: u64,
+    /// `-1` without metadata
+    pieces_have: i64,
     pieces_num: i64,
     reannounce: i64,
     seeds: i64,
     seeds_total: i64,
-    total_size: u64,
+    /// `-1` without metadata
+    total_size: i64,
     up_speed_avg: i64,
     up_speed: i64,
 }
@@ -147,7 +216,8 @@ pub enum State {
     MissingFiles,
     #[serde(rename = "uploading")]
     Uploading,
-    #[serde(rename = "stoppedUP")]
+    /// Reported as `pausedUP` before qbittorrent 5.0
+    #[serde(rename = "stoppedUP", alias = "pausedUP")]
     StoppedUP,
     #[serde(rename = "queuedUP")]
     QueuedUP,
@@ -163,7 +233,8 @@ pub enum State {
     Downloading,
     #[serde(rename = "metaDL")]
     MetaDL,
-    #[serde(rename = "stoppedDL")]
+    /// Reported as `pausedDL` before qbittorrent 5.0
+    #[serde(rename = "stoppedDL", alias = "pausedDL")]
     StoppedDL,
     #[serde(rename = "queuedDL")]
     QueuedDL,
@@ -193,13 +264,14 @@ pub struct TransferInfo {
     connection_status: ConnectionStatus,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize, Copy, Clone, Default, Eq, PartialEq)]
 pub enum ConnectionStatus {
     #[serde(rename = "connected")]
     Connected,
     #[serde(rename = "firewalled")]
     Firewalled,
     #[serde(rename = "disconnected")]
+    #[default]
     Disconnected,
 }
 
@@ -232,28 +304,363 @@ pub enum AlternateLimits {
 
 #[derive(Debug, Deserialize, Serialize, Getters)]
 pub struct TorrentInfo {
+    #[serde(default)]
     hash: Hash,
+    /// File id used by `TorrentApi::set_file_priority`, not reported by older
+    /// versions where the position in the file list is the id
+    index: Option<u64>,
+    /// Path of the file relative to the torrent's root
     name: String,
     size: i64,
     progress: f64,
-    priority: i16,
+    priority: FilePriority,
     is_seed: Option<bool>,
     piece_range: Vec<i64>,
     availability: f64,
 }
 
-#[derive(Debug, Deserialize, Default, Getters)]
+#[derive(Debug, Deserialize, Serialize, Copy, Clone, Eq, PartialEq)]
+#[serde(from = "i64", into = "i64")]
+pub enum FilePriority {
+    DoNotDownload,
+    Normal,
+    High,
+    Maximal,
+    /// A priority without a variant, e.g. `4` or the mixed priority `-1`
+    Other(i64),
+}
+
+impl From<i64> for FilePriority {
+    fn from(f: i64) -> Self {
+        match f {
+            0 => FilePriority::DoNotDownload,
+            1 => FilePriority::Normal,
+            6 => FilePriority::High,
+            7 => FilePriority::Maximal,
+            f => FilePriority::Other(f),
+        }
+    }
+}
+
+impl From<FilePriority> for i64 {
+    fn from(f: FilePriority) -> Self {
+        match f {
+            FilePriority::DoNotDownload => 0,
+            FilePriority::Normal => 1,
+            FilePriority::High => 6,
+            FilePriority::Maximal => 7,
+            FilePriority::Other(f) => f,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum PieceState {
+    NotDownloaded,
+    Downloading,
+    Downloaded,
+}
+
+/// Download state of every piece of a torrent, packed into two bits per piece.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct PieceMap {
+    len: usize,
+    bits: Vec<u8>,
+}
+
+impl PieceMap {
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn get(&self, piece: usize) -> Option<PieceState> {
+        if piece >= self.len {
+            return None;
+        }
+        match (self.bits[piece / 4] >> (piece % 4 * 2)) & 0b11 {
+            0 => Some(PieceState::NotDownloaded),
+            1 => Some(PieceState::Downloading),
+            _ => Some(PieceState::Downloaded),
+        }
+    }
+
+    pub fn push(&mut self, state: PieceState) {
+        let value = match state {
+            PieceState::NotDownloaded => 0,
+            PieceState::Downloading => 1,
+            PieceState::Downloaded => 2,
+        };
+        if self.len == self.bits.len() * 4 {
+            self.bits.push(0);
+        }
+        self.bits[self.len / 4] |= value << (self.len % 4 * 2);
+        self.len += 1;
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = PieceState> + '_ {
+        (0..self.len).filter_map(|i| self.get(i))
+    }
+
+    /// Number of pieces in `state`.
+    pub fn count(&self, state: PieceState) -> usize {
+        self.iter().filter(|s| *s == state).count()
+    }
+
+    /// Contiguous runs of pieces in `state`.
+    pub fn ranges(&self, state: PieceState) -> Vec<Range<usize>> {
+        let mut ret = Vec::new();
+        let mut start = None;
+        for (i, s) in self.iter().enumerate() {
+            match (s == state, start) {
+                (true, None) => start = Some(i),
+                (false, Some(first)) => {
+                    ret.push(first..i);
+                    start = None;
+                }
+                _ => {}
+            }
+        }
+        if let Some(first) = start {
+            ret.push(first..self.len);
+        }
+        ret
+    }
+
+    /// Fraction (0.0 - 1.0) of the pieces spanned by `file` which have been
+    /// downloaded.
+    pub fn file_coverage(&self, file: &TorrentInfo) -> f64 {
+        let (first, last) = match file.piece_range.as_slice() {
+            [first, last] if *first >= 0 && first <= last => (*first as usize, *last as usize),
+            _ => return 0.0,
+        };
+        let last = last.min(self.len.saturating_sub(1));
+        if first > last {
+            return 0.0;
+        }
+        let downloaded = (first..=last)
+            .filter(|i| self.get(*i) == Some(PieceState::Downloaded))
+            .count();
+        downloaded as f64 / (last - first + 1) as f64
+    }
+}
+
+impl FromIterator<PieceState> for PieceMap {
+    fn from_iter<I: IntoIterator<Item = PieceState>>(iter: I) -> Self {
+        let mut map = PieceMap::default();
+        for state in iter {
+            map.push(state);
+        }
+        map
+    }
+}
+
+impl<'de> Deserialize<'de> for PieceMap {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let states = Vec::<u8>::deserialize(deserializer)?;
+        states
+            .into_iter()
+            .map(|s| match s {
+                0 => Ok(PieceState::NotDownloaded),
+                1 => Ok(PieceState::Downloading),
+                2 => Ok(PieceState::Downloaded),
+                _ => Err(serde::de::Error::custom(format!(
+                    "invalid piece state: {}",
+                    s
+                ))),
+            })
+            .collect()
+    }
+}
+
+/// SHA-1 digest of a single piece.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct PieceHash(pub [u8; 20]);
+
+impl std::fmt::Display for PieceHash {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for b in self.0 {
+            write!(f, "{:02x}", b)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::str::FromStr for PieceHash {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("invalid piece hash: {:?}", s);
+        if s.len() != 40 || !s.is_ascii() {
+            return Err(invalid());
+        }
+        let mut digest = [0; 20];
+        for (i, b) in digest.iter_mut().enumerate() {
+            *b = u8::from_str_radix(&s[i * 2..i * 2 + 2], 16).map_err(|_| invalid())?;
+        }
+        Ok(PieceHash(digest))
+    }
+}
+
+impl<'de> Deserialize<'de> for PieceHash {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+#[derive(Debug, Deserialize, Default, Getters, Clone)]
 pub struct Category {
     name: String,
     #[serde(rename = "savePath")]
     save_path: String,
 }
 
-#[derive(Debug, Deserialize)]
-pub struct ServerState {}
+/// Global server statistics from the `server_state` portion of maindata
+#[derive(Debug, Deserialize, Default, Clone, Getters)]
+#[serde(default)]
+pub struct ServerState {
+    /// Data downloaded over all sessions (bytes)
+    alltime_dl: i64,
+    /// Data uploaded over all sessions (bytes)
+    alltime_ul: i64,
+    /// Global download rate (bytes/s)
+    dl_info_speed: i64,
+    /// Data downloaded this session (bytes)
+    dl_info_data: i64,
+    /// Global upload rate (bytes/s)
+    up_info_speed: i64,
+    /// Data uploaded this session (bytes)
+    up_info_data: i64,
+    /// Download rate limit (bytes/s)
+    dl_rate_limit: i64,
+    /// Upload rate limit (bytes/s)
+    up_rate_limit: i64,
+    /// Share ratio over all sessions
+    #[serde(deserialize_with = "f64_from_str")]
+    global_ratio: f64,
+    /// Free space in the default save path (bytes)
+    free_space_on_disk: i64,
+    /// Number of queued disk I/O jobs
+    queued_io_jobs: i64,
+    /// Total size of the queued disk I/O jobs (bytes)
+    total_queued_size: i64,
+    /// Average time a disk I/O job spends queued (ms)
+    average_time_queue: i64,
+    /// Size of the disk cache buffers (bytes)
+    total_buffers_size: i64,
+    /// Read cache hits (percent)
+    #[serde(deserialize_with = "f64_from_str")]
+    read_cache_hits: f64,
+    /// Read cache overload (percent)
+    #[serde(deserialize_with = "f64_from_str")]
+    read_cache_overload: f64,
+    /// Write cache overload (percent)
+    #[serde(deserialize_with = "f64_from_str")]
+    write_cache_overload: f64,
+    /// Data wasted this session (bytes)
+    total_wasted_session: i64,
+    total_peer_connections: i64,
+    /// DHT nodes connected to
+    dht_nodes: i64,
+    connection_status: ConnectionStatus,
+    /// Whether the alternative speed limits are in effect
+    use_alt_speed_limits: bool,
+    /// Suggested interval between maindata requests (ms)
+    refresh_interval: i64,
+    /// Whether torrent queueing is enabled
+    queueing: bool,
+    use_subcategories: bool,
+    /// Not reported by older versions
+    last_external_address_v4: String,
+    /// Not reported by older versions
+    last_external_address_v6: String,
+}
 
-#[derive(Debug, Deserialize)]
-pub struct Peer {}
+impl ServerState {
+    /// Ratio of uploaded to downloaded data in this session.
+    pub fn session_ratio(&self) -> f64 {
+        if self.dl_info_data > 0 {
+            self.up_info_data as f64 / self.dl_info_data as f64
+        } else {
+            0.0
+        }
+    }
+
+    /// Whether the disk cache could not keep up, i.e. the read or write cache
+    /// overload exceeds `threshold` percent.
+    pub fn cache_overloaded(&self, threshold: f64) -> bool {
+        self.read_cache_overload > threshold || self.write_cache_overload > threshold
+    }
+}
+
+/// Some numeric server state values are reported as strings.
+fn f64_from_str<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<f64, D::Error> {
+    match Value::deserialize(deserializer)? {
+        Value::Number(n) => n
+            .as_f64()
+            .ok_or_else(|| serde::de::Error::custom("invalid number")),
+        Value::String(s) if s == "∞" => Ok(f64::INFINITY),
+        Value::String(s) => s.parse().map_err(serde::de::Error::custom),
+        other => Err(serde::de::Error::custom(format!(
+            "expected a number, got {}",
+            other
+        ))),
+    }
+}
+
+/// A single response from `/api/v2/sync/torrentPeers`.
+///
+/// Like `MainData`, unless `full_update` is set the peers only contain the
+/// fields which changed. Use [`crate::sync::PeerSyncState`] to merge them.
+#[derive(Debug, Deserialize, Getters)]
+pub struct TorrentPeers {
+    pub(crate) rid: u64,
+    #[serde(default)]
+    pub(crate) full_update: bool,
+    /// Whether the peer flags are reported
+    pub(crate) show_flags: Option<bool>,
+    /// Changed peers keyed by `ip:port`
+    #[serde(default)]
+    pub(crate) peers: HashMap<String, Map<String, Value>>,
+    #[serde(default)]
+    pub(crate) peers_removed: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Default, Getters)]
+#[serde(default)]
+pub struct Peer {
+    ip: String,
+    port: u16,
+    /// Client name and version from the handshake
+    client: String,
+    /// Client derived from the peer id, not reported by older versions
+    peer_id_client: String,
+    /// Connection type, e.g. `BT`, `μTP` or `Web`
+    connection: String,
+    /// Only set when peer countries are resolved
+    country: String,
+    country_code: String,
+    /// Short flags, e.g. `D U K E`
+    flags: String,
+    /// Newline separated description of the flags
+    flags_desc: String,
+    /// Peer progress (0.0 - 1.0)
+    progress: f64,
+    /// Download rate from the peer (bytes/s)
+    dl_speed: i64,
+    /// Upload rate to the peer (bytes/s)
+    up_speed: i64,
+    downloaded: i64,
+    uploaded: i64,
+    /// How many of our missing pieces the peer has (0.0 - 1.0)
+    relevance: f64,
+    /// Newline separated names of the files the peer is transferring
+    files: String,
+}
 
 #[derive(Debug, Deserialize, Getters)]
 pub struct BuildInfo {
@@ -264,19 +671,431 @@ pub struct BuildInfo {
     bitness: i64,
 }
 
-#[derive(Deserialize, Debug)]
-pub struct Preferences {}
+/// Application preferences from `/api/v2/app/preferences`
+///
+/// Fields which are not reported by the connected qbittorrent version are left
+/// at their default value.
+#[derive(Deserialize, Serialize, Debug, Clone, Default, Getters)]
+#[serde(default)]
+pub struct Preferences {
+    // Downloads
+    /// Default save path for torrents
+    save_path: String,
+    /// Keep incomplete torrents in `temp_path`
+    temp_path_enabled: bool,
+    /// Path for incomplete torrents
+    temp_path: String,
+    /// Copy .torrent files to this directory
+    export_dir: String,
+    /// Copy .torrent files of finished downloads to this directory
+    export_dir_fin: String,
+    /// Pre-allocate disk space for all files
+    preallocate_all: bool,
+    /// Append `.!qB` to incomplete files
+    incomplete_files_ext: bool,
+    /// Delete .torrent files afterwards (0 never, 1 if added, 2 always)
+    auto_delete_mode: i64,
+    /// Automatically add `add_trackers` to new downloads
+    add_trackers_enabled: bool,
+    /// Newline separated trackers added to new downloads
+    add_trackers: String,
+    /// Run an external program on torrent completion
+    autorun_enabled: bool,
+    /// Program run on torrent completion
+    autorun_program: String,
+    /// Automatic torrent management is the default for new torrents
+    auto_tmm_enabled: bool,
+    /// Relocate torrent when its category changes
+    torrent_changed_tmm_enabled: bool,
+    /// Relocate affected torrents when the default save path changes
+    save_path_changed_tmm_enabled: bool,
+    /// Relocate affected torrents when their category save path changes
+    category_changed_tmm_enabled: bool,
+
+    // Connection
+    /// Port for incoming connections
+    listen_port: i64,
+    /// Use a random port on every startup
+    random_port: bool,
+    /// Use UPnP/NAT-PMP to forward `listen_port`
+    upnp: bool,
+    /// Allowed protocols (0 TCP and uTP, 1 TCP, 2 uTP)
+    bittorrent_protocol: i64,
+    /// Maximum global number of connections, -1 for unlimited
+    max_connec: i64,
+    /// Maximum number of connections per torrent, -1 for unlimited
+    max_connec_per_torrent: i64,
+    /// Maximum global number of upload slots, -1 for unlimited
+    max_uploads: i64,
+    /// Maximum number of upload slots per torrent, -1 for unlimited
+    max_uploads_per_torrent: i64,
+    /// Lower bound of the outgoing port range, 0 to disable
+    outgoing_ports_min: i64,
+    /// Upper bound of the outgoing port range, 0 to disable
+    outgoing_ports_max: i64,
+    /// Network interface used for connections
+    current_network_interface: String,
+    /// Interface address used for connections
+    current_interface_address: String,
+
+    // Speed
+    /// Global download limit (bytes/s), -1 for unlimited
+    dl_limit: i64,
+    /// Global upload limit (bytes/s), -1 for unlimited
+    up_limit: i64,
+    /// Alternative download limit (bytes/s)
+    alt_dl_limit: i64,
+    /// Alternative upload limit (bytes/s)
+    alt_up_limit: i64,
+    /// Apply rate limits to the uTP protocol
+    limit_utp_rate: bool,
+    /// Apply rate limits to transport overhead
+    limit_tcp_overhead: bool,
+    /// Apply rate limits to peers on the LAN
+    limit_lan_peers: bool,
+    /// Switch to the alternative limits on a schedule
+    scheduler_enabled: bool,
+    /// Scheduler start hour
+    schedule_from_hour: i64,
+    /// Scheduler start minute
+    schedule_from_min: i64,
+    /// Scheduler end hour
+    schedule_to_hour: i64,
+    /// Scheduler end minute
+    schedule_to_min: i64,
+    /// Scheduler days (0 every day, 1 weekdays, 2 weekends, 3-9 Monday-Sunday)
+    scheduler_days: i64,
+
+    // BitTorrent
+    /// DHT is enabled
+    dht: bool,
+    /// PeX is enabled
+    pex: bool,
+    /// Local peer discovery is enabled
+    lsd: bool,
+    /// Encryption mode (0 prefer, 1 force on, 2 force off)
+    encryption: i64,
+    /// Anonymous mode is enabled
+    anonymous_mode: bool,
+    /// Torrent queueing is enabled
+    queueing_enabled: bool,
+    /// Maximum number of active downloads
+    max_active_downloads: i64,
+    /// Maximum number of active uploads
+    max_active_uploads: i64,
+    /// Maximum number of active torrents
+    max_active_torrents: i64,
+    /// Exclude slow torrents from the active limits
+    dont_count_slow_torrents: bool,
+    /// Download rate below which a torrent is slow (KiB/s)
+    slow_torrent_dl_rate_threshold: i64,
+    /// Upload rate below which a torrent is slow (KiB/s)
+    slow_torrent_ul_rate_threshold: i64,
+    /// Seconds a torrent must be slow before it is inactive
+    slow_torrent_inactive_timer: i64,
+    /// Apply the global share ratio limit
+    max_ratio_enabled: bool,
+    /// Global share ratio limit
+    max_ratio: f64,
+    /// Apply the global seeding time limit
+    max_seeding_time_enabled: bool,
+    /// Global seeding time limit (minutes)
+    max_seeding_time: i64,
+    /// Action when a limit is reached (0 pause, 1 remove, 2 remove with files, 3 super seeding)
+    max_ratio_act: i64,
+
+    // Proxy
+    /// Proxy type
+    proxy_type: ProxyType,
+    /// Proxy host
+    proxy_ip: String,
+    /// Proxy port
+    proxy_port: i64,
+    /// Proxy requires authentication
+    proxy_auth_enabled: bool,
+    /// Proxy username
+    proxy_username: String,
+    /// Proxy password
+    proxy_password: String,
+    /// Use the proxy for peer connections
+    proxy_peer_connections: bool,
+    /// Use the proxy for torrents only
+    proxy_torrents_only: bool,
+
+    // IP filtering
+    /// IP filtering is enabled
+    ip_filter_enabled: bool,
+    /// Path to the IP filter file
+    ip_filter_path: String,
+    /// Apply the IP filter to trackers
+    ip_filter_trackers: bool,
+    /// Newline separated banned IP addresses
+    #[serde(rename = "banned_IPs")]
+    banned_ips: String,
+
+    // WebUI
+    /// Current locale
+    locale: String,
+    /// Semicolon separated allowed domains
+    web_ui_domain_list: String,
+    /// Address the WebUI listens on
+    web_ui_address: String,
+    /// Port the WebUI listens on
+    web_ui_port: i64,
+    /// Use UPnP/NAT-PMP to forward `web_ui_port`
+    web_ui_upnp: bool,
+    /// WebUI username
+    web_ui_username: String,
+    /// WebUI password, write only
+    #[getter(skip)]
+    web_ui_password: String,
+    /// Serve the WebUI over HTTPS
+    use_https: bool,
+    /// Skip authentication for clients on localhost
+    bypass_local_auth: bool,
+    /// Skip authentication for whitelisted subnets
+    bypass_auth_subnet_whitelist_enabled: bool,
+    /// Newline separated whitelisted subnets
+    bypass_auth_subnet_whitelist: String,
+    /// Seconds before an idle session expires
+    web_ui_session_timeout: i64,
+    /// Failed logins before a client is banned
+    web_ui_max_auth_fail_count: i64,
+    /// Seconds a client is banned for
+    web_ui_ban_duration: i64,
+    /// Clickjacking protection is enabled
+    web_ui_clickjacking_protection_enabled: bool,
+    /// CSRF protection is enabled
+    web_ui_csrf_protection_enabled: bool,
+    /// Serve an alternative WebUI
+    alternative_webui_enabled: bool,
+    /// Path to the alternative WebUI
+    alternative_webui_path: String,
+    /// Update a dynamic DNS domain
+    dyndns_enabled: bool,
+    /// Dynamic DNS service (0 DynDNS, 1 NOIP)
+    dyndns_service: i64,
+    /// Dynamic DNS username
+    dyndns_username: String,
+    /// Dynamic DNS password
+    dyndns_password: String,
+    /// Dynamic DNS domain
+    dyndns_domain: String,
+
+    // RSS
+    /// Feed refresh interval (minutes)
+    rss_refresh_interval: i64,
+    /// Maximum number of stored articles per feed
+    rss_max_articles_per_feed: i64,
+    /// Feeds are fetched
+    rss_processing_enabled: bool,
+    /// Auto download rules are applied
+    rss_auto_downloading_enabled: bool,
+    /// Download REPACK/PROPER episodes
+    rss_download_repack_proper_episodes: bool,
+    /// Newline separated smart episode filters
+    rss_smart_episode_filters: String,
+
+    // Email notifications
+    /// Send an email on torrent completion
+    mail_notification_enabled: bool,
+    /// Sender address
+    mail_notification_sender: String,
+    /// Recipient address
+    mail_notification_email: String,
+    /// SMTP server
+    mail_notification_smtp: String,
+    /// Use SSL for the SMTP connection
+    mail_notification_ssl_enabled: bool,
+    /// SMTP server requires authentication
+    mail_notification_auth_enabled: bool,
+    /// SMTP username
+    mail_notification_username: String,
+    /// SMTP password
+    mail_notification_password: String,
+
+    // Advanced
+    /// Disk cache size (MiB), -1 for automatic
+    disk_cache: i64,
+    /// Disk cache expiry interval (seconds)
+    disk_cache_ttl: i64,
+    /// Use the OS cache
+    enable_os_cache: bool,
+    /// Number of asynchronous I/O threads
+    async_io_threads: i64,
+    /// Maximum number of open files
+    file_pool_size: i64,
+    /// Memory used for checking torrents (MiB)
+    checking_memory_use: i64,
+    /// Send buffer watermark (KiB)
+    send_buffer_watermark: i64,
+    /// Send buffer low watermark (KiB)
+    send_buffer_low_watermark: i64,
+    /// Send buffer watermark factor (percent)
+    send_buffer_watermark_factor: i64,
+    /// Resume data save interval (minutes)
+    save_resume_data_interval: i64,
+    /// Recheck torrents on completion
+    recheck_completed_torrents: bool,
+    /// Resolve peer countries
+    resolve_peer_countries: bool,
+    /// Announce to all trackers in a tier
+    announce_to_all_trackers: bool,
+    /// Announce to all tiers
+    announce_to_all_tiers: bool,
+    /// IP address reported to trackers
+    announce_ip: String,
+    /// Run the embedded tracker
+    enable_embedded_tracker: bool,
+    /// Port of the embedded tracker
+    embedded_tracker_port: i64,
+    /// Allow multiple connections from the same IP
+    enable_multi_connections_from_same_ip: bool,
+    /// Upload slots behavior (0 fixed, 1 upload rate based)
+    upload_slots_behavior: i64,
+    /// Upload choking algorithm (0 round-robin, 1 fastest upload, 2 anti-leech)
+    upload_choking_algorithm: i64,
+}
+
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub enum ProxyType {
+    #[default]
+    None,
+    Http,
+    Socks5,
+    Socks4,
+}
+
+impl ProxyType {
+    /// The integer older versions use, which also encodes whether the proxy
+    /// requires authentication.
+    pub(crate) fn code(self, auth: bool) -> i64 {
+        match (self, auth) {
+            (ProxyType::None, _) => 0,
+            (ProxyType::Http, false) => 1,
+            (ProxyType::Socks5, false) => 2,
+            (ProxyType::Http, true) => 3,
+            (ProxyType::Socks5, true) => 4,
+            (ProxyType::Socks4, _) => 5,
+        }
+    }
+}
 
-#[derive(Deserialize, Debug, Getters)]
+impl Serialize for ProxyType {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(match self {
+            ProxyType::None => "None",
+            ProxyType::Http => "HTTP",
+            ProxyType::Socks5 => "SOCKS5",
+            ProxyType::Socks4 => "SOCKS4",
+        })
+    }
+}
+
+impl<'de> Deserialize<'de> for ProxyType {
+    /// Older versions report the proxy type as an integer, where the `2`/`4`
+    /// and `1`/`3` pairs only differ in whether authentication is used.
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        match Value::deserialize(deserializer)? {
+            Value::Number(n) => match n.as_i64() {
+                Some(1) | Some(3) => Ok(ProxyType::Http),
+                Some(2) | Some(4) => Ok(ProxyType::Socks5),
+                Some(5) => Ok(ProxyType::Socks4),
+                _ => Ok(ProxyType::None),
+            },
+            Value::String(s) => match s.as_str() {
+                "HTTP" => Ok(ProxyType::Http),
+                "SOCKS5" => Ok(ProxyType::Socks5),
+                "SOCKS4" => Ok(ProxyType::Socks4),
+                _ => Ok(ProxyType::None),
+            },
+            other => Err(serde::de::Error::custom(format!(
+                "invalid proxy type: {}",
+                other
+            ))),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, Getters)]
 pub struct Log {
     id: u64,
     message: String,
+    #[getter(skip)]
     timestamp: u64,
     #[serde(rename = "type")]
-    level: u64,
+    level: LogLevel,
+}
+
+impl Log {
+    pub fn timestamp(&self) -> SystemTime {
+        log_time(self.timestamp)
+    }
 }
 
-#[derive(Deserialize, Serialize, Debug, Clone, Default, Hash)]
+#[derive(Deserialize, Debug, Clone, Getters)]
+pub struct PeerLog {
+    id: u64,
+    ip: String,
+    #[getter(skip)]
+    timestamp: u64,
+    /// Whether the connection was blocked, or the peer banned
+    blocked: bool,
+    reason: String,
+}
+
+impl PeerLog {
+    pub fn timestamp(&self) -> SystemTime {
+        log_time(self.timestamp)
+    }
+}
+
+/// Log timestamps are in milliseconds on older versions and in seconds on
+/// newer ones. Values beyond the year 5000 in seconds are milliseconds.
+fn log_time(timestamp: u64) -> SystemTime {
+    if timestamp > 100_000_000_000 {
+        UNIX_EPOCH + Duration::from_millis(timestamp)
+    } else {
+        UNIX_EPOCH + Duration::from_secs(timestamp)
+    }
+}
+
+/// Severity of a `Log` entry, ordered from least to most severe.
+#[derive(Deserialize, Serialize, Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[serde(try_from = "u64", into = "u64")]
+pub enum LogLevel {
+    Normal,
+    Info,
+    Warning,
+    Critical,
+}
+
+impl TryFrom<u64> for LogLevel {
+    type Error = String;
+
+    fn try_from(f: u64) -> Result<Self, Self::Error> {
+        match f {
+            1 => Ok(LogLevel::Normal),
+            2 => Ok(LogLevel::Info),
+            4 => Ok(LogLevel::Warning),
+            8 => Ok(LogLevel::Critical),
+            _ => Err(format!("invalid log level: {}", f)),
+        }
+    }
+}
+
+impl From<LogLevel> for u64 {
+    fn from(f: LogLevel) -> Self {
+        match f {
+            LogLevel::Normal => 1,
+            LogLevel::Info => 2,
+            LogLevel::Warning => 4,
+            LogLevel::Critical => 8,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Default, Hash, PartialEq, Eq)]
 #[serde(transparent)]
 pub struct Hash {
     pub(crate) hash: String,
@@ -294,3 +1113,127 @@ impl std::ops::Deref for Hash {
         &self.hash
     }
 }
+
+/// WebUI API version from `/api/v2/app/webapiVersion`, e.g. `2.11.2`.
+///
+/// This is not the qbittorrent version, qbittorrent 5.0 has API version
+/// `2.11.0`. Versions compare component-wise.
+#[derive(Debug, Copy, Clone, Default, Hash, PartialEq, Eq, PartialOrd, Ord, Getters)]
+pub struct ApiVersion {
+    major: u32,
+    minor: u32,
+    patch: u32,
+}
+
+impl ApiVersion {
+    pub const fn new(major: u32, minor: u32, patch: u32) -> Self {
+        Self {
+            major,
+            minor,
+            patch,
+        }
+    }
+}
+
+impl std::fmt::Display for ApiVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+impl std::str::FromStr for ApiVersion {
+    type Err = crate::error::Error;
+
+    /// Parse `major[.minor[.patch]]`, missing components are zero.
+    fn from_str(s: &str) -> crate::error::Result<Self> {
+        let invalid = || crate::error::Error::InvalidVersion(s.into());
+        let trimmed = s.trim();
+        let trimmed = trimmed.strip_prefix('v').unwrap_or(trimmed);
+        let mut parts = [0; 3];
+        for (i, part) in trimmed.split('.').enumerate() {
+            let slot = parts.get_mut(i).ok_or_else(invalid)?;
+            *slot = part.parse().map_err(|_| invalid())?;
+        }
+        Ok(Self::new(parts[0], parts[1], parts[2]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use PieceState::*;
+
+    fn file(piece_range: [i64; 2]) -> TorrentInfo {
+        serde_json::from_value(serde_json::json!({
+            "name": "file",
+            "size": 1,
+            "progress": 0.0,
+            "priority": 1,
+            "piece_range": piece_range,
+            "availability": 0.0,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn piece_map_empty() {
+        let map = PieceMap::default();
+        assert!(map.is_empty());
+        assert_eq!(map.get(0), None);
+        for state in [NotDownloaded, Downloading, Downloaded] {
+            assert_eq!(map.count(state), 0);
+            assert!(map.ranges(state).is_empty());
+        }
+        assert_eq!(map.file_coverage(&file([0, 3])), 0.0);
+    }
+
+    #[test]
+    fn piece_map_ranges() {
+        let map: PieceMap = [
+            Downloaded,
+            NotDownloaded,
+            Downloading,
+            Downloaded,
+            Downloaded,
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(map.len(), 5);
+        assert_eq!(map.get(2), Some(Downloading));
+        // The last run ends at the last piece.
+        assert_eq!(map.ranges(Downloaded), [0..1, 3..5]);
+        assert_eq!(map.ranges(NotDownloaded), vec![1..2]);
+        assert_eq!(map.ranges(Downloading), vec![2..3]);
+
+        let map: PieceMap = (0..9).map(|_| Downloaded).collect();
+        assert_eq!(map.ranges(Downloaded), vec![0..9]);
+    }
+
+    #[test]
+    fn piece_map_file_coverage() {
+        let map: PieceMap = [
+            Downloaded,
+            Downloaded,
+            NotDownloaded,
+            Downloaded,
+            Downloading,
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(map.file_coverage(&file([0, 4])), 3.0 / 5.0);
+        // Files which start and end mid-piece share their first and last
+        // piece with the neighbouring files.
+        assert_eq!(map.file_coverage(&file([1, 3])), 2.0 / 3.0);
+        assert_eq!(map.file_coverage(&file([3, 3])), 1.0);
+        // Ranges past the end of the map are clamped.
+        assert_eq!(map.file_coverage(&file([3, 7])), 1.0 / 2.0);
+        assert_eq!(map.file_coverage(&file([6, 7])), 0.0);
+
+        // Zero-length files span no pieces, qbittorrent reports the range as
+        // ending before it starts.
+        for range in [[2, 1], [0, -1], [-1, -1]] {
+            let coverage = map.file_coverage(&file(range));
+            assert_eq!(coverage, 0.0, "{:?}", range);
+        }
+    }
+}
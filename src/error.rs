@@ -1,3 +1,4 @@
+use crate::data::ApiVersion;
 use thiserror::Error as ThisError;
 
 #[derive(ThisError, Debug)]
@@ -8,6 +9,10 @@ pub enum Error {
     ToStringError(#[from] reqwest::header::ToStrError),
     #[error("Serde json could not correctly deserialize: {0}")]
     SerdeJson(#[from] serde_json::Error),
+    #[error("Could not url encode the form: {0}")]
+    UrlEncode(#[from] serde_urlencoded::ser::Error),
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
     #[error("Url parse error: {0}")]
     UrlParseError(#[from] url::ParseError),
     #[error("Header value was malformed: {0}")]
@@ -20,6 +25,206 @@ pub enum Error {
     SliceError,
     #[error("Bad response from server")]
     BadResponse,
+    #[error("Invalid .torrent file: {0}")]
+    InvalidTorrent(String),
+    #[error("Invalid magnet uri: {0}")]
+    InvalidMagnet(String),
+    #[error("qbittorrent failed to add the torrent")]
+    AddTorrentFailed,
+    #[error("Torrent creation failed: {0}")]
+    TorrentCreationFailed(String),
+    #[error("Timed out waiting for qbittorrent")]
+    Timeout,
+    #[error("Invalid WebUI API version: {0}")]
+    InvalidVersion(String),
+    #[error("Requires WebUI API {needs}, the server has {have}")]
+    Unsupported { needs: ApiVersion, have: ApiVersion },
+    #[error("Torrent not found at {endpoint}")]
+    TorrentNotFound { endpoint: String },
+    #[error("Torrent file is not valid: {body}")]
+    InvalidTorrentFile { body: String },
+    #[error("Invalid category name: {body}")]
+    InvalidCategoryName { body: String },
+    #[error("Category already exists: {body}")]
+    CategoryExists { body: String },
+    #[error("Category does not exist: {body}")]
+    CategoryNotFound { body: String },
+    #[error("Torrent queueing is not enabled")]
+    QueueingDisabled,
+    #[error("Invalid url at {endpoint}: {body}")]
+    InvalidUrl { endpoint: String, body: String },
+    #[error("Tracker url already exists or was not found at {endpoint}: {body}")]
+    TrackerConflict { endpoint: String, body: String },
+    #[error("Invalid file priority or file id: {body}")]
+    InvalidFilePriority { body: String },
+    #[error("File not found or torrent metadata not downloaded yet: {body}")]
+    FileNotFound { body: String },
+    #[error("Invalid path at {endpoint}: {body}")]
+    InvalidPath { endpoint: String, body: String },
+    #[error("No write access to the save path: {body}")]
+    NoWriteAccess { body: String },
+    #[error("Too many searches are running")]
+    TooManySearches,
+    #[error("Search job not found at {endpoint}")]
+    SearchJobNotFound { endpoint: String },
+    #[error("Torrent creator task not found at {endpoint}")]
+    TaskNotFound { endpoint: String },
+    #[error("Bad request to {endpoint}: {body}")]
+    BadRequest { endpoint: String, body: String },
+    #[error("Forbidden to access {endpoint} - is the session still valid or the IP banned?")]
+    Forbidden { endpoint: String },
+    #[error("Not found at {endpoint}")]
+    NotFound { endpoint: String },
+    #[error("Conflict at {endpoint}: {body}")]
+    Conflict { endpoint: String, body: String },
+    #[error("Unsupported media type at {endpoint}: {body}")]
+    UnsupportedMediaType { endpoint: String, body: String },
+    #[error("Unexpected status {status} from {endpoint}: {body}")]
+    Status {
+        endpoint: String,
+        status: u16,
+        body: String,
+    },
+}
+
+impl Error {
+    /// Map a non-success http status from `endpoint` to an error.
+    ///
+    /// The WebUI API reports failures through the status code, and what a
+    /// code means depends on the endpoint: `409` from `createCategory` means
+    /// the category exists, from `setCategory` that it does not. Statuses the
+    /// WebUI API documents for an endpoint are mapped to their own variants,
+    /// others to the variant of the status code. `403` from `auth/login` means
+    /// the client IP is banned after too many failed logins.
+    pub(crate) fn from_status(endpoint: &str, status: reqwest::StatusCode, body: String) -> Self {
+        let name = endpoint.strip_prefix("/api/v2/").unwrap_or(endpoint);
+        let endpoint = endpoint.to_string();
+        match (name, status.as_u16()) {
+            ("torrents/add", 415) => Error::InvalidTorrentFile { body },
+            ("torrents/createCategory" | "torrents/editCategory", 400) => {
+                Error::InvalidCategoryName { body }
+            }
+            ("torrents/createCategory", 409) if body.starts_with("Incorrect category name") => {
+                Error::InvalidCategoryName { body }
+            }
+            ("torrents/createCategory", 409) => Error::CategoryExists { body },
+            ("torrents/editCategory" | "torrents/setCategory", 409) => {
+                Error::CategoryNotFound { body }
+            }
+            (
+                "torrents/increasePrio"
+                | "torrents/decreasePrio"
+                | "torrents/topPrio"
+                | "torrents/bottomPrio",
+                409,
+            ) => Error::QueueingDisabled,
+            (
+                "torrents/properties"
+                | "torrents/trackers"
+                | "torrents/webseeds"
+                | "torrents/files"
+                | "torrents/pieceStates"
+                | "torrents/pieceHashes"
+                | "torrents/addTrackers"
+                | "torrents/editTracker"
+                | "torrents/removeTrackers"
+                | "torrents/addWebSeeds"
+                | "torrents/editWebSeed"
+                | "torrents/removeWebSeeds"
+                | "torrents/filePrio"
+                | "torrents/renameFile"
+                | "torrents/renameFolder"
+                | "sync/torrentPeers",
+                404,
+            ) => Error::TorrentNotFound { endpoint },
+            (
+                "torrents/editTracker"
+                | "torrents/addWebSeeds"
+                | "torrents/editWebSeed"
+                | "torrents/removeWebSeeds",
+                400,
+            ) => Error::InvalidUrl { endpoint, body },
+            ("torrents/editTracker" | "torrents/removeTrackers", 409) => {
+                Error::TrackerConflict { endpoint, body }
+            }
+            ("torrents/filePrio", 400) => Error::InvalidFilePriority { body },
+            ("torrents/filePrio", 409) => Error::FileNotFound { body },
+            (
+                "torrents/renameFile" | "torrents/renameFolder" | "torrents/setLocation",
+                400 | 409,
+            ) => Error::InvalidPath { endpoint, body },
+            ("torrents/setLocation", 403) => Error::NoWriteAccess { body },
+            ("search/start", 409) => Error::TooManySearches,
+            ("search/stop" | "search/status" | "search/results" | "search/delete", 404) => {
+                Error::SearchJobNotFound { endpoint }
+            }
+            (
+                "torrentcreator/status"
+                | "torrentcreator/torrentFile"
+                | "torrentcreator/deleteTask",
+                404,
+            ) => Error::TaskNotFound { endpoint },
+            (_, 400) => Error::BadRequest { endpoint, body },
+            (_, 403) => Error::Forbidden { endpoint },
+            (_, 404) => Error::NotFound { endpoint },
+            (_, 409) => Error::Conflict { endpoint, body },
+            (_, 415) => Error::UnsupportedMediaType { endpoint, body },
+            (_, status) => Error::Status {
+                endpoint,
+                status,
+                body,
+            },
+        }
+    }
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::StatusCode;
+
+    fn map(endpoint: &str, status: StatusCode, body: &str) -> Error {
+        Error::from_status(endpoint, status, body.into())
+    }
+
+    #[test]
+    fn from_status_by_endpoint() {
+        let conflict = StatusCode::CONFLICT;
+        let err = map(
+            "/api/v2/torrents/createCategory",
+            conflict,
+            "Unable to create",
+        );
+        assert!(matches!(err, Error::CategoryExists { .. }));
+        let err = map(
+            "/api/v2/torrents/createCategory",
+            conflict,
+            "Incorrect category name",
+        );
+        assert!(matches!(err, Error::InvalidCategoryName { .. }));
+        let err = map(
+            "/api/v2/torrents/setCategory",
+            conflict,
+            "Incorrect category name",
+        );
+        assert!(matches!(err, Error::CategoryNotFound { .. }));
+        let err = map("/api/v2/torrents/topPrio", conflict, "");
+        assert!(matches!(err, Error::QueueingDisabled));
+        let err = map("/api/v2/rss/addFeed", conflict, "exists");
+        assert!(matches!(err, Error::Conflict { .. }));
+
+        let err = map("/api/v2/torrents/files", StatusCode::NOT_FOUND, "");
+        assert!(matches!(err, Error::TorrentNotFound { .. }));
+        let err = map("/api/v2/search/status", StatusCode::NOT_FOUND, "");
+        assert!(matches!(err, Error::SearchJobNotFound { .. }));
+        let err = map("/api/v2/torrents/stop", StatusCode::NOT_FOUND, "");
+        assert!(matches!(err, Error::NotFound { .. }));
+
+        let err = map("/api/v2/torrents/setLocation", StatusCode::FORBIDDEN, "");
+        assert!(matches!(err, Error::NoWriteAccess { .. }));
+        let err = map("/api/v2/auth/login", StatusCode::FORBIDDEN, "banned");
+        assert!(matches!(err, Error::Forbidden { .. }));
+    }
+}
@@ -0,0 +1,392 @@
+//! Torrent events derived from successive `/api/v2/sync/maindata` updates
+
+use crate::api::Api;
+use crate::data::{MainData, State, Torrent};
+use crate::error::Result;
+use crate::sync::SyncState;
+use futures::stream::{self, Stream};
+use log::*;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+
+#[derive(Debug, Clone)]
+pub enum TorrentEvent {
+    Added(Torrent),
+    Removed(Torrent),
+    /// Progress reached 100%
+    Finished(Torrent),
+    StateChanged {
+        torrent: Torrent,
+        from: State,
+    },
+    /// State changed to `State::Error` or `State::MissingFiles`
+    Errored(Torrent),
+    CategoryChanged {
+        torrent: Torrent,
+        from: String,
+    },
+    TagsChanged {
+        torrent: Torrent,
+        from: String,
+    },
+}
+
+fn is_error(state: State) -> bool {
+    matches!(state, State::Error | State::MissingFiles)
+}
+
+/// Events between two states of the same torrent.
+///
+/// `Finished` is reported whenever progress reaches 100%, including after a
+/// recheck. `TorrentWatcher` reports it only once per torrent.
+pub fn diff(old: &Torrent, new: &Torrent) -> Vec<TorrentEvent> {
+    let mut events = Vec::new();
+    if old.state() != new.state() {
+        events.push(TorrentEvent::StateChanged {
+            torrent: new.clone(),
+            from: *old.state(),
+        });
+        if is_error(*new.state()) && !is_error(*old.state()) {
+            events.push(TorrentEvent::Errored(new.clone()));
+        }
+    }
+    if *old.progress() < 1.0 && *new.progress() >= 1.0 {
+        events.push(TorrentEvent::Finished(new.clone()));
+    }
+    if old.category() != new.category() {
+        events.push(TorrentEvent::CategoryChanged {
+            torrent: new.clone(),
+            from: old.category().clone(),
+        });
+    }
+    if old.tags() != new.tags() {
+        events.push(TorrentEvent::TagsChanged {
+            torrent: new.clone(),
+            from: old.tags().clone(),
+        });
+    }
+    events
+}
+
+/// Keeps a `SyncState` and reports how the torrents changed with every update.
+///
+/// The first update only records the initial state and reports no events.
+/// `Finished` is reported the first time a torrent completes, not again when
+/// it completes after a recheck.
+#[derive(Debug, Default)]
+pub struct TorrentWatcher {
+    state: SyncState,
+    /// Whether the initial state has been recorded.
+    primed: bool,
+    /// Hashes of the torrents which have completed.
+    finished: HashSet<String>,
+}
+
+impl TorrentWatcher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn state(&self) -> &SyncState {
+        &self.state
+    }
+
+    /// Request the changes since the last update and return the resulting
+    /// events.
+    pub async fn poll(&mut self, api: &Api) -> Result<Vec<TorrentEvent>> {
+        let data = api.sync_maindata(self.state.rid()).await?;
+        self.apply(data)
+    }
+
+    /// Apply a maindata response and return the resulting events.
+    pub fn apply(&mut self, data: MainData) -> Result<Vec<TorrentEvent>> {
+        // Removed torrents are not listed in a full update, so keep a copy of
+        // every torrent which might change.
+        let old: HashMap<String, Torrent> = if data.full_update {
+            self.state
+                .torrents()
+                .map(|t| (t.hash.hash.clone(), t.clone()))
+                .collect()
+        } else {
+            data.torrents
+                .keys()
+                .chain(&data.torrents_removed)
+                .filter_map(|h| self.state.torrent(h).map(|t| (h.clone(), t.clone())))
+                .collect()
+        };
+        let changed: Vec<String> = data.torrents.keys().cloned().collect();
+
+        self.state.apply(data)?;
+        if !std::mem::replace(&mut self.primed, true) {
+            self.finished = self
+                .state
+                .torrents()
+                .filter(|t| *t.progress() >= 1.0)
+                .map(|t| t.hash.hash.clone())
+                .collect();
+            return Ok(Vec::new());
+        }
+
+        let mut events = Vec::new();
+        for hash in changed {
+            if !old.contains_key(&hash) {
+                if let Some(new) = self.state.torrent(&hash) {
+                    if *new.progress() >= 1.0 {
+                        self.finished.insert(hash);
+                    }
+                    events.push(TorrentEvent::Added(new.clone()));
+                }
+            }
+        }
+        for (hash, old) in old {
+            match self.state.torrent(&hash) {
+                Some(new) => events.extend(diff(&old, new)),
+                None => {
+                    self.finished.remove(&hash);
+                    events.push(TorrentEvent::Removed(old));
+                }
+            }
+        }
+        events.retain(|event| match event {
+            TorrentEvent::Finished(t) => self.finished.insert(t.hash.hash.clone()),
+            _ => true,
+        });
+        Ok(events)
+    }
+}
+
+impl Api {
+    /// Poll maindata every `interval` and yield the torrent events.
+    ///
+    /// Failed requests are yielded as errors and retried after `interval`, the
+    /// stream itself never ends. Drop the stream to stop polling.
+    pub fn torrent_events(
+        &self,
+        interval: Duration,
+    ) -> impl Stream<Item = Result<TorrentEvent>> + '_ {
+        let state = (TorrentWatcher::new(), VecDeque::new(), false);
+        stream::unfold(
+            state,
+            move |(mut watcher, mut pending, mut wait)| async move {
+                loop {
+                    if let Some(event) = pending.pop_front() {
+                        return Some((Ok(event), (watcher, pending, wait)));
+                    }
+                    if wait {
+                        tokio::time::sleep(interval).await;
+                    }
+                    wait = true;
+                    match watcher.poll(self).await {
+                        Ok(events) => pending.extend(events),
+                        Err(e) => return Some((Err(e), (watcher, pending, wait))),
+                    }
+                }
+            },
+        )
+    }
+}
+
+/// Poll maindata every `interval` on a background task and send the torrent
+/// events to a broadcast channel with room for `capacity` events.
+///
+/// Use `Receiver::resubscribe` for additional receivers. The task stops once
+/// all receivers have been dropped.
+pub fn spawn_torrent_events(
+    api: Arc<Api>,
+    interval: Duration,
+    capacity: usize,
+) -> (broadcast::Receiver<TorrentEvent>, JoinHandle<()>) {
+    let (sender, receiver) = broadcast::channel(capacity);
+    let handle = tokio::spawn(async move {
+        let mut watcher = TorrentWatcher::new();
+        loop {
+            match watcher.poll(&api).await {
+                Ok(events) => {
+                    for event in events {
+                        if sender.send(event).is_err() {
+                            return;
+                        }
+                    }
+                }
+                Err(e) => warn!("torrent events: {}", e),
+            }
+            if sender.receiver_count() == 0 {
+                return;
+            }
+            tokio::time::sleep(interval).await;
+        }
+    });
+    (receiver, handle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const DEBIAN: &str = "8c4adbf9ebe66f1d804fb6a4fb9b74966c3ab609";
+    const UBUNTU: &str = "e2467cbf021192c241367b892230dc1e05c0580e";
+    const ARCH: &str = "2aa4f5a7e209e54b32803d43670971c4c8caaa05";
+
+    fn full() -> serde_json::Value {
+        serde_json::from_str(include_str!(
+            "../tests/fixtures/4-stop-start/sync_maindata.json"
+        ))
+        .unwrap()
+    }
+
+    fn main_data(value: serde_json::Value) -> MainData {
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn primed() -> TorrentWatcher {
+        let mut watcher = TorrentWatcher::new();
+        assert!(watcher.apply(main_data(full())).unwrap().is_empty());
+        watcher
+    }
+
+    fn names(events: &[TorrentEvent]) -> Vec<String> {
+        let mut names: Vec<String> = events
+            .iter()
+            .map(|event| {
+                match event {
+                    TorrentEvent::Added(_) => "Added",
+                    TorrentEvent::Removed(_) => "Removed",
+                    TorrentEvent::Finished(_) => "Finished",
+                    TorrentEvent::StateChanged { .. } => "StateChanged",
+                    TorrentEvent::Errored(_) => "Errored",
+                    TorrentEvent::CategoryChanged { .. } => "CategoryChanged",
+                    TorrentEvent::TagsChanged { .. } => "TagsChanged",
+                }
+                .to_string()
+            })
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn added() {
+        let mut watcher = primed();
+        let mut torrent = full()["torrents"][ARCH].clone();
+        torrent["name"] = "copy".into();
+        let data =
+            json!({"rid": 2, "torrents": {"0123456789abcdef0123456789abcdef01234567": torrent}});
+        let events = watcher.apply(main_data(data)).unwrap();
+        assert_eq!(names(&events), ["Added"]);
+        assert!(matches!(&events[0], TorrentEvent::Added(t) if t.name() == "copy"));
+    }
+
+    #[test]
+    fn category_and_tags_changed() {
+        let mut watcher = primed();
+        let data = json!({"rid": 2, "torrents": {
+            DEBIAN: {"category": "tv"},
+            UBUNTU: {"tags": "iso"},
+        }});
+        let events = watcher.apply(main_data(data)).unwrap();
+        assert_eq!(names(&events), ["CategoryChanged", "TagsChanged"]);
+        for event in events {
+            match event {
+                TorrentEvent::CategoryChanged { torrent, from } => {
+                    assert_eq!(torrent.category(), "tv");
+                    assert_eq!(from, "linux");
+                }
+                TorrentEvent::TagsChanged { torrent, from } => {
+                    assert_eq!(torrent.tags(), "iso");
+                    assert_eq!(from, "iso, lts");
+                }
+                _ => unreachable!(),
+            }
+        }
+    }
+
+    #[test]
+    fn errored() {
+        let mut watcher = primed();
+        let data = json!({"rid": 2, "torrents": {UBUNTU: {"state": "missingFiles"}}});
+        let events = watcher.apply(main_data(data)).unwrap();
+        assert_eq!(names(&events), ["Errored", "StateChanged"]);
+
+        // Moving between error states is not a new error.
+        let data = json!({"rid": 3, "torrents": {UBUNTU: {"state": "error"}}});
+        let events = watcher.apply(main_data(data)).unwrap();
+        assert_eq!(names(&events), ["StateChanged"]);
+    }
+
+    #[test]
+    fn finished_once() {
+        let mut watcher = primed();
+        let data = json!({"rid": 2, "torrents": {UBUNTU: {"progress": 1.0}}});
+        let events = watcher.apply(main_data(data)).unwrap();
+        assert_eq!(names(&events), ["Finished"]);
+
+        // A recheck drops the progress, completing again is not reported.
+        let data = json!({"rid": 3, "torrents": {UBUNTU: {"progress": 0.5}}});
+        assert!(watcher.apply(main_data(data)).unwrap().is_empty());
+        let data = json!({"rid": 4, "torrents": {UBUNTU: {"progress": 1.0}}});
+        assert!(watcher.apply(main_data(data)).unwrap().is_empty());
+        let data = json!({"rid": 5, "torrents": {DEBIAN: {"progress": 0.5}}});
+        assert!(watcher.apply(main_data(data)).unwrap().is_empty());
+        let data = json!({"rid": 6, "torrents": {DEBIAN: {"progress": 1.0}}});
+        assert!(watcher.apply(main_data(data)).unwrap().is_empty());
+    }
+
+    #[test]
+    fn full_update_reset() {
+        let mut watcher = primed();
+
+        // The server forgot the rid and resends everything: unchanged torrents
+        // report nothing, missing ones are removed.
+        let mut data = full();
+        data["rid"] = 7.into();
+        data["torrents"].as_object_mut().unwrap().remove(ARCH);
+        data["torrents"][UBUNTU]["state"] = "stalledDL".into();
+        let events = watcher.apply(main_data(data)).unwrap();
+        assert_eq!(names(&events), ["Removed", "StateChanged"]);
+        assert_eq!(watcher.state().rid(), 7);
+        assert_eq!(watcher.state().torrents().count(), 2);
+
+        let mut data = full();
+        data["rid"] = 8.into();
+        let events = watcher.apply(main_data(data)).unwrap();
+        assert_eq!(names(&events), ["Added", "StateChanged"]);
+    }
+
+    #[cfg(feature = "mock")]
+    #[tokio::test]
+    async fn spawned_events_stop() {
+        use crate::mock::{MockServer, MockTorrent};
+
+        let server = MockServer::start().await.unwrap();
+        let api = Api::auth(&server.url(), "admin", "adminadmin")
+            .await
+            .unwrap();
+        let (mut receiver, handle) =
+            spawn_torrent_events(Arc::new(api), Duration::from_millis(10), 16);
+        // Wait until the initial state has been served, the torrent added
+        // afterwards is only in the next update.
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while server.maindata_requests() == 0 {
+                tokio::time::sleep(Duration::from_millis(1)).await;
+            }
+        })
+        .await
+        .unwrap();
+        server.add_torrent(MockTorrent::new(ARCH, "arch"));
+        let event = tokio::time::timeout(Duration::from_secs(5), receiver.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(matches!(event, TorrentEvent::Added(t) if t.name() == "arch"));
+
+        drop(receiver);
+        tokio::time::timeout(Duration::from_secs(5), handle)
+            .await
+            .unwrap()
+            .unwrap();
+    }
+}
@@ -1,9 +1,19 @@
 #![doc = include_str!("../README.md")]
 
 mod api;
+mod bencode;
 pub mod data;
 mod error;
+pub mod events;
+pub mod magnet;
+pub mod metainfo;
+#[cfg(feature = "mock")]
+pub mod mock;
 pub mod queries;
+pub mod rss;
+pub mod search;
+pub mod sync;
+pub mod torrent_creator;
 pub mod traits;
 
 pub use api::Api;
@@ -0,0 +1,319 @@
+//! Parsing and building magnet uris
+
+use crate::data::Hash;
+use crate::error::{Error, Result};
+use crate::metainfo::to_hex;
+use data_encoding::BASE32;
+use derive_builder::Builder;
+use derive_getters::Getters;
+use std::fmt;
+use std::ops::RangeInclusive;
+use std::str::FromStr;
+use url::form_urlencoded::byte_serialize;
+use url::Url;
+
+/// A BitTorrent magnet link.
+///
+/// Parse with `str::parse` or build with `MagnetBuilder`, the uri is available
+/// through `Display`. At least one of the v1 and v2 infohashes is always set.
+#[derive(Debug, Clone, Eq, PartialEq, Builder, Getters)]
+#[builder(setter(into, strip_option), build_fn(validate = "Self::validate"))]
+pub struct Magnet {
+    /// v1 infohash as 40 lowercase hex digits, `xt=urn:btih:`
+    #[builder(default)]
+    info_hash_v1: Option<String>,
+    /// v2 infohash as 64 lowercase hex digits, `xt=urn:btmh:`
+    #[builder(default)]
+    info_hash_v2: Option<String>,
+    /// Display name, `dn`
+    #[builder(default)]
+    display_name: Option<String>,
+    /// Tracker urls, `tr`
+    #[builder(default, setter(each(name = "tracker", into)))]
+    trackers: Vec<String>,
+    /// Web seed urls, `ws`
+    #[builder(default, setter(each(name = "web_seed", into)))]
+    web_seeds: Vec<String>,
+    /// Exact length in bytes, `xl`
+    #[builder(default)]
+    exact_length: Option<u64>,
+    /// Indexes of the files to download, `so`
+    #[builder(default, setter(each(name = "select", into)))]
+    select_only: Vec<RangeInclusive<u64>>,
+}
+
+fn is_hex(s: &str, len: usize) -> bool {
+    s.len() == len && s.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+fn is_lower_hex(s: &str, len: usize) -> bool {
+    is_hex(s, len) && !s.bytes().any(|b| b.is_ascii_uppercase())
+}
+
+impl MagnetBuilder {
+    fn validate(&self) -> std::result::Result<(), String> {
+        let v1 = self.info_hash_v1.clone().flatten();
+        let v2 = self.info_hash_v2.clone().flatten();
+        if v1.is_none() && v2.is_none() {
+            return Err("a v1 or v2 infohash is required".into());
+        }
+        if v1.is_some_and(|h| !is_lower_hex(&h, 40)) {
+            return Err("the v1 infohash must be 40 lowercase hex digits".into());
+        }
+        if v2.is_some_and(|h| !is_lower_hex(&h, 64)) {
+            return Err("the v2 infohash must be 64 lowercase hex digits".into());
+        }
+        Ok(())
+    }
+}
+
+impl Magnet {
+    /// The hash qbittorrent identifies the torrent by, the v1 infohash or the
+    /// truncated v2 infohash.
+    pub fn hash(&self) -> Hash {
+        match (&self.info_hash_v1, &self.info_hash_v2) {
+            (Some(v1), _) => v1.to_ascii_lowercase().into(),
+            (None, Some(v2)) => v2[..40].to_ascii_lowercase().into(),
+            (None, None) => unreachable!("magnet without an infohash"),
+        }
+    }
+}
+
+impl From<&Magnet> for Hash {
+    fn from(f: &Magnet) -> Self {
+        f.hash()
+    }
+}
+
+fn parse_select_only(s: &str) -> Option<Vec<RangeInclusive<u64>>> {
+    s.split(',')
+        .map(|part| match part.split_once('-') {
+            Some((first, last)) => {
+                let (first, last) = (first.parse().ok()?, last.parse().ok()?);
+                (first <= last).then_some(first..=last)
+            }
+            None => part.parse().ok().map(|i| i..=i),
+        })
+        .collect()
+}
+
+impl FromStr for Magnet {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || Error::InvalidMagnet(s.into());
+        let url = Url::parse(s).map_err(|_| invalid())?;
+        if url.scheme() != "magnet" {
+            return Err(invalid());
+        }
+
+        let mut magnet = Magnet {
+            info_hash_v1: None,
+            info_hash_v2: None,
+            display_name: None,
+            trackers: Vec::new(),
+            web_seeds: Vec::new(),
+            exact_length: None,
+            select_only: Vec::new(),
+        };
+        for (key, value) in url.query_pairs() {
+            // Indexed parameters such as `tr.1` are equivalent to `tr`.
+            let key = key.split('.').next().unwrap_or_default();
+            match key {
+                "xt" => {
+                    if let Some(btih) = value.strip_prefix("urn:btih:") {
+                        let hex = match btih.len() {
+                            40 if is_hex(btih, 40) => btih.to_ascii_lowercase(),
+                            32 => to_hex(
+                                &BASE32
+                                    .decode(btih.to_ascii_uppercase().as_bytes())
+                                    .map_err(|_| invalid())?,
+                            ),
+                            _ => return Err(invalid()),
+                        };
+                        magnet.info_hash_v1 = Some(hex);
+                    } else if let Some(btmh) = value.strip_prefix("urn:btmh:") {
+                        // Multihash, 0x12 is sha2-256 and 0x20 the digest length.
+                        match btmh.strip_prefix("1220") {
+                            Some(hex) if is_hex(hex, 64) => {
+                                magnet.info_hash_v2 = Some(hex.to_ascii_lowercase())
+                            }
+                            _ => return Err(invalid()),
+                        }
+                    }
+                }
+                "dn" => magnet.display_name = Some(value.into_owned()),
+                "tr" => magnet.trackers.push(value.into_owned()),
+                "ws" => magnet.web_seeds.push(value.into_owned()),
+                "xl" => magnet.exact_length = Some(value.parse().map_err(|_| invalid())?),
+                "so" => magnet.select_only = parse_select_only(&value).ok_or_else(invalid)?,
+                _ => {}
+            }
+        }
+
+        if magnet.info_hash_v1.is_none() && magnet.info_hash_v2.is_none() {
+            return Err(invalid());
+        }
+        Ok(magnet)
+    }
+}
+
+impl fmt::Display for Magnet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut params = Vec::new();
+        if let Some(v1) = &self.info_hash_v1 {
+            params.push(format!("xt=urn:btih:{}", v1));
+        }
+        if let Some(v2) = &self.info_hash_v2 {
+            params.push(format!("xt=urn:btmh:1220{}", v2));
+        }
+        if let Some(name) = &self.display_name {
+            params.push(format!(
+                "dn={}",
+                byte_serialize(name.as_bytes()).collect::<String>()
+            ));
+        }
+        if let Some(length) = self.exact_length {
+            params.push(format!("xl={}", length));
+        }
+        for tracker in &self.trackers {
+            params.push(format!(
+                "tr={}",
+                byte_serialize(tracker.as_bytes()).collect::<String>()
+            ));
+        }
+        for seed in &self.web_seeds {
+            params.push(format!(
+                "ws={}",
+                byte_serialize(seed.as_bytes()).collect::<String>()
+            ));
+        }
+        if !self.select_only.is_empty() {
+            let ranges: Vec<String> = self
+                .select_only
+                .iter()
+                .map(|r| match r.start() == r.end() {
+                    true => r.start().to_string(),
+                    false => format!("{}-{}", r.start(), r.end()),
+                })
+                .collect();
+            params.push(format!("so={}", ranges.join(",")));
+        }
+        write!(f, "magnet:?{}", params.join("&"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const V1: &str = "ddcff160ea6f013def4a83687e178c5afb41c035";
+    const V2: &str = "fbed6b48c4a2cf1237e45b94cfa470286602c20c430529d4f9d943914671e22c";
+
+    #[test]
+    fn parse_btih() {
+        let magnet: Magnet = format!("magnet:?xt=urn:btih:{}", V1.to_uppercase())
+            .parse()
+            .unwrap();
+        assert_eq!(magnet.info_hash_v1().as_deref(), Some(V1));
+
+        // The same infohash in base32.
+        for base32 in [
+            "3XH7CYHKN4AT332KQNUH4F4MLL5UDQBV",
+            "3xh7cyhkn4at332kqnuh4f4mll5udqbv",
+        ] {
+            let magnet: Magnet = format!("magnet:?xt=urn:btih:{}", base32).parse().unwrap();
+            assert_eq!(magnet.info_hash_v1().as_deref(), Some(V1));
+            assert_eq!(*magnet.hash(), V1);
+        }
+    }
+
+    #[test]
+    fn parse_btmh() {
+        let magnet: Magnet = format!("magnet:?xt=urn:btmh:1220{}", V2).parse().unwrap();
+        assert_eq!(magnet.info_hash_v1(), &None);
+        assert_eq!(magnet.info_hash_v2().as_deref(), Some(V2));
+        assert_eq!(*magnet.hash(), V2[..40]);
+
+        let hybrid = format!("magnet:?xt=urn:btih:{}&xt=urn:btmh:1220{}", V1, V2);
+        let magnet: Magnet = hybrid.parse().unwrap();
+        assert_eq!(magnet.info_hash_v2().as_deref(), Some(V2));
+        assert_eq!(*magnet.hash(), V1);
+        assert_eq!(magnet.to_string(), hybrid);
+    }
+
+    #[test]
+    fn parse_params() {
+        let uri = format!(
+            "magnet:?xt=urn:btih:{}&dn=a+b%2Fc&xl=42&tr.1=udp%3A%2F%2Fone&tr.2=http://two/announce?k=v&ws=http%3A%2F%2Fseed&so=0,2-4,7",
+            V1
+        );
+        let magnet: Magnet = uri.parse().unwrap();
+        assert_eq!(magnet.display_name().as_deref(), Some("a b/c"));
+        assert_eq!(*magnet.exact_length(), Some(42));
+        assert_eq!(magnet.trackers(), &["udp://one", "http://two/announce?k=v"]);
+        assert_eq!(magnet.web_seeds(), &["http://seed"]);
+        assert_eq!(magnet.select_only(), &[0..=0, 2..=4, 7..=7]);
+    }
+
+    #[test]
+    fn round_trip() {
+        let magnet = MagnetBuilder::default()
+            .info_hash_v1(V1)
+            .display_name("Name with spaces & symbols?")
+            .exact_length(6203355136u64)
+            .tracker("udp://tracker.example.org:1337/announce")
+            .tracker("http://example.org/announce?passkey=a&b=c")
+            .web_seed("https://example.org/files/")
+            .select(0..=0)
+            .select(3..=5)
+            .build()
+            .unwrap();
+        let uri = magnet.to_string();
+        assert!(uri.starts_with(&format!("magnet:?xt=urn:btih:{}&dn=Name+with", V1)));
+        assert!(uri.ends_with("&so=0,3-5"));
+        assert_eq!(uri.parse::<Magnet>().unwrap(), magnet);
+    }
+
+    #[test]
+    fn invalid() {
+        let invalid = [
+            format!("http://example.org/?xt=urn:btih:{}", V1),
+            "magnet:?dn=no+hash".into(),
+            format!("magnet:?xt=urn:btih:{}", &V1[1..]),
+            "magnet:?xt=urn:btih:3XH7CYHKN4AT332KQNUH4F4MLL5UDQB1".into(),
+            format!("magnet:?xt=urn:btmh:1114{}", V2),
+            format!("magnet:?xt=urn:btih:{}&xl=-1", V1),
+            format!("magnet:?xt=urn:btih:{}&so=4-2", V1),
+            format!("magnet:?xt=urn:btih:{}&so=1,x", V1),
+        ];
+        for uri in invalid {
+            assert!(uri.parse::<Magnet>().is_err(), "{}", uri);
+        }
+        assert!(MagnetBuilder::default().build().is_err());
+        assert!(MagnetBuilder::default()
+            .info_hash_v1("xyz")
+            .build()
+            .is_err());
+    }
+
+    #[test]
+    fn builder_rejects_uppercase() {
+        let err = MagnetBuilder::default()
+            .info_hash_v1(V1.to_uppercase())
+            .build()
+            .unwrap_err();
+        assert!(err.to_string().contains("lowercase"), "{}", err);
+        assert!(MagnetBuilder::default()
+            .info_hash_v2(V2.to_uppercase())
+            .build()
+            .is_err());
+
+        let magnet = MagnetBuilder::default()
+            .info_hash_v1(V1)
+            .info_hash_v2(V2)
+            .build()
+            .unwrap();
+        assert_eq!(magnet.to_string().parse::<Magnet>().unwrap(), magnet);
+    }
+}
@@ -0,0 +1,371 @@
+//! Parsing .torrent metainfo files (v1, v2 and hybrid)
+
+use crate::bencode::{self, Dict, Value};
+use crate::data::{Hash, Torrent, TorrentInfo};
+use crate::error::{Error, Result};
+use derive_getters::Getters;
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+use std::path::Path;
+
+pub(crate) fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum MetaVersion {
+    V1,
+    V2,
+    /// Both v1 and v2 metadata describing the same content
+    Hybrid,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Getters)]
+pub struct MetaFile {
+    /// Path as reported by qbittorrent in `TorrentInfo::name`, prefixed with
+    /// the torrent name for multi-file torrents
+    path: String,
+    length: u64,
+}
+
+/// Contents of a .torrent file.
+#[derive(Debug, Clone, Getters)]
+pub struct Metainfo {
+    name: String,
+    version: MetaVersion,
+    /// Files in the order qbittorrent lists them, without padding files
+    files: Vec<MetaFile>,
+    piece_length: u64,
+    num_pieces: u64,
+    /// Tracker urls grouped by tier
+    trackers: Vec<Vec<String>>,
+    web_seeds: Vec<String>,
+    private: bool,
+    comment: Option<String>,
+    created_by: Option<String>,
+    /// Seconds since epoch
+    creation_date: Option<i64>,
+    /// SHA-1 of the info dictionary, as 40 hex digits
+    info_hash_v1: Option<String>,
+    /// SHA-256 of the info dictionary, as 64 hex digits
+    info_hash_v2: Option<String>,
+}
+
+fn invalid(msg: &str) -> Error {
+    Error::InvalidTorrent(msg.into())
+}
+
+fn text(value: Option<&Value<'_>>) -> Option<String> {
+    value
+        .and_then(|v| v.as_bytes())
+        .map(|b| String::from_utf8_lossy(b).into_owned())
+}
+
+/// Join path components, every component must be a string.
+fn join_path<'a>(prefix: &str, parts: impl IntoIterator<Item = &'a Value<'a>>) -> Result<String> {
+    let mut path = prefix.to_string();
+    for part in parts {
+        let part = text(Some(part)).ok_or_else(|| invalid("invalid file path"))?;
+        if !path.is_empty() {
+            path.push('/');
+        }
+        path.push_str(&part);
+    }
+    Ok(path)
+}
+
+/// The `length` of a file, which must not be negative.
+fn file_length(file: &Dict<'_>) -> Result<u64> {
+    let length = file.get("length").and_then(|l| l.as_int());
+    let length = length.ok_or_else(|| invalid("missing file length"))?;
+    u64::try_from(length).map_err(|_| invalid("invalid file length"))
+}
+
+fn v1_files(info: &Dict<'_>, name: &str) -> Result<Vec<MetaFile>> {
+    let files = match info.get("files").and_then(|f| f.as_list()) {
+        Some(files) => files,
+        None => {
+            return Ok(vec![MetaFile {
+                path: name.into(),
+                length: file_length(info)?,
+            }]);
+        }
+    };
+
+    let mut ret = Vec::new();
+    for file in files {
+        let file = file
+            .as_dict()
+            .ok_or_else(|| invalid("invalid file entry"))?;
+        let attr = file
+            .get("attr")
+            .and_then(|a| a.as_bytes())
+            .unwrap_or_default();
+        if attr.contains(&b'p') {
+            continue;
+        }
+        let path = file.get("path").and_then(|p| p.as_list());
+        let path = path.ok_or_else(|| invalid("invalid file entry"))?;
+        ret.push(MetaFile {
+            path: join_path(name, path)?,
+            length: file_length(file)?,
+        });
+    }
+    Ok(ret)
+}
+
+fn v2_files(tree: &Dict<'_>, path: &str, ret: &mut Vec<MetaFile>) -> Result<()> {
+    for (name, node) in tree.iter() {
+        let node = node.as_dict().ok_or_else(|| invalid("invalid file tree"))?;
+        if name.is_empty() {
+            ret.push(MetaFile {
+                path: path.into(),
+                length: file_length(node)?,
+            });
+        } else {
+            let path = join_path(path, [&Value::Bytes(name)])?;
+            v2_files(node, &path, ret)?;
+        }
+    }
+    Ok(())
+}
+
+impl Metainfo {
+    pub fn from_bytes(data: &[u8]) -> Result<Self> {
+        let root = bencode::decode(data).map_err(Error::InvalidTorrent)?;
+        let root = root.as_dict().ok_or_else(|| invalid("not a dictionary"))?;
+        let info = root
+            .get("info")
+            .and_then(|info| info.as_dict())
+            .ok_or_else(|| invalid("missing info dictionary"))?;
+
+        let name = text(info.get("name")).ok_or_else(|| invalid("missing name"))?;
+        let piece_length = info.get("piece length").and_then(|l| l.as_int());
+        let piece_length = piece_length
+            .filter(|l| *l > 0)
+            .ok_or_else(|| invalid("missing piece length"))? as u64;
+
+        let pieces = info.get("pieces").and_then(|p| p.as_bytes());
+        let file_tree = match info.get("meta version").and_then(|v| v.as_int()) {
+            Some(2) => info.get("file tree").and_then(|t| t.as_dict()),
+            _ => None,
+        };
+        let version = match (pieces, file_tree) {
+            (Some(_), Some(_)) => MetaVersion::Hybrid,
+            (Some(_), None) => MetaVersion::V1,
+            (None, Some(_)) => MetaVersion::V2,
+            (None, None) => return Err(invalid("missing pieces or file tree")),
+        };
+
+        let (files, num_pieces) = match (pieces, file_tree) {
+            (Some(pieces), _) => {
+                if pieces.len() % 20 != 0 {
+                    return Err(invalid("invalid pieces length"));
+                }
+                (v1_files(info, &name)?, (pieces.len() / 20) as u64)
+            }
+            (None, Some(tree)) => {
+                let mut files = Vec::new();
+                v2_files(tree, "", &mut files)?;
+                // The file tree of a multi-file torrent does not include the
+                // torrent name as the root folder.
+                if !(files.len() == 1 && files[0].path == name) {
+                    for file in &mut files {
+                        file.path = format!("{}/{}", name, file.path);
+                    }
+                }
+                // Files in a v2 torrent are aligned to piece boundaries.
+                let num_pieces = files.iter().map(|f| f.length.div_ceil(piece_length)).sum();
+                (files, num_pieces)
+            }
+            (None, None) => unreachable!(),
+        };
+
+        let mut trackers = Vec::new();
+        if let Some(tiers) = root.get("announce-list").and_then(|l| l.as_list()) {
+            for tier in tiers {
+                let tier: Vec<String> = tier
+                    .as_list()
+                    .unwrap_or_default()
+                    .iter()
+                    .filter_map(|url| text(Some(url)))
+                    .collect();
+                if !tier.is_empty() {
+                    trackers.push(tier);
+                }
+            }
+        }
+        if trackers.is_empty() {
+            if let Some(announce) = text(root.get("announce")) {
+                trackers.push(vec![announce]);
+            }
+        }
+
+        let web_seeds = match root.get("url-list") {
+            Some(Value::List(urls)) => urls.iter().filter_map(|url| text(Some(url))).collect(),
+            url => text(url).into_iter().collect(),
+        };
+
+        let v1 = version != MetaVersion::V2;
+        let v2 = version != MetaVersion::V1;
+        Ok(Metainfo {
+            name,
+            version,
+            files,
+            piece_length,
+            num_pieces,
+            trackers,
+            web_seeds,
+            private: info.get("private").and_then(|p| p.as_int()) == Some(1),
+            comment: text(root.get("comment")),
+            created_by: text(root.get("created by")),
+            creation_date: root.get("creation date").and_then(|d| d.as_int()),
+            info_hash_v1: v1.then(|| to_hex(&Sha1::digest(info.raw()))),
+            info_hash_v2: v2.then(|| to_hex(&Sha256::digest(info.raw()))),
+        })
+    }
+
+    /// Read and parse a .torrent file from disk.
+    pub async fn read(path: impl AsRef<Path>) -> Result<Self> {
+        let data = tokio::fs::read(path).await?;
+        Self::from_bytes(&data)
+    }
+
+    /// The hash qbittorrent identifies the torrent by, the v1 infohash or the
+    /// truncated v2 infohash.
+    pub fn hash(&self) -> Hash {
+        match (&self.info_hash_v1, &self.info_hash_v2) {
+            (Some(v1), _) => v1.clone().into(),
+            (None, Some(v2)) => v2[..40].to_string().into(),
+            (None, None) => unreachable!("metainfo without an infohash"),
+        }
+    }
+
+    pub fn total_size(&self) -> u64 {
+        self.files.iter().map(|f| f.length).sum()
+    }
+
+    /// Whether `torrent` was added from this metainfo.
+    pub fn matches(&self, torrent: &Torrent) -> bool {
+        torrent.hash == self.hash()
+    }
+
+    /// The file described by a `TorrentApi::contents` entry.
+    pub fn file(&self, info: &TorrentInfo) -> Option<&MetaFile> {
+        self.files.iter().find(|f| &f.path == info.name())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Wrap an info dictionary into a .torrent file.
+    fn torrent(info: &[u8]) -> Vec<u8> {
+        let mut data = b"d8:announce23:http://tracker/announce4:info".to_vec();
+        data.extend_from_slice(info);
+        data.push(b'e');
+        data
+    }
+
+    // The expected infohashes were computed independently with Python's
+    // hashlib over the info dictionaries.
+
+    const V1: &[u8] = b"d6:lengthi12345e4:name8:test.txt12:piece lengthi16384e\
+        6:pieces20:aaaaaaaaaaaaaaaaaaaae";
+
+    const V2: &[u8] = b"d9:file treed8:test.txtd0:d6:lengthi12345e\
+        11:pieces root32:bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbeee\
+        12:meta versioni2e4:name8:test.txt12:piece lengthi16384ee";
+
+    const HYBRID: &[u8] = b"d9:file treed\
+        1:ad0:d6:lengthi16384e11:pieces root32:ccccccccccccccccccccccccccccccccee\
+        1:bd0:d6:lengthi100e11:pieces root32:ddddddddddddddddddddddddddddddddeee\
+        5:filesl\
+        d6:lengthi16384e4:pathl1:aee\
+        d4:attr1:p6:lengthi16284e4:pathl4:.pad5:16284ee\
+        d6:lengthi100e4:pathl1:beee\
+        12:meta versioni2e4:name3:dir12:piece lengthi16384e\
+        6:pieces40:aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaae";
+
+    #[test]
+    fn v1() {
+        let metainfo = Metainfo::from_bytes(&torrent(V1)).unwrap();
+        assert_eq!(*metainfo.version(), MetaVersion::V1);
+        assert_eq!(
+            metainfo.info_hash_v1().as_deref(),
+            Some("ddcff160ea6f013def4a83687e178c5afb41c035")
+        );
+        assert_eq!(metainfo.info_hash_v2(), &None);
+        assert_eq!(*metainfo.hash(), "ddcff160ea6f013def4a83687e178c5afb41c035");
+        assert_eq!(metainfo.name(), "test.txt");
+        assert_eq!(metainfo.total_size(), 12345);
+        assert_eq!(*metainfo.num_pieces(), 1);
+        assert_eq!(
+            metainfo.trackers(),
+            &[vec!["http://tracker/announce".to_string()]]
+        );
+    }
+
+    #[test]
+    fn v2() {
+        let metainfo = Metainfo::from_bytes(&torrent(V2)).unwrap();
+        assert_eq!(*metainfo.version(), MetaVersion::V2);
+        assert_eq!(metainfo.info_hash_v1(), &None);
+        let v2 = "fbed6b48c4a2cf1237e45b94cfa470286602c20c430529d4f9d943914671e22c";
+        assert_eq!(metainfo.info_hash_v2().as_deref(), Some(v2));
+        assert_eq!(*metainfo.hash(), v2[..40]);
+        assert_eq!(metainfo.files()[0].path(), "test.txt");
+        assert_eq!(*metainfo.num_pieces(), 1);
+    }
+
+    #[test]
+    fn hybrid() {
+        let metainfo = Metainfo::from_bytes(&torrent(HYBRID)).unwrap();
+        assert_eq!(*metainfo.version(), MetaVersion::Hybrid);
+        assert_eq!(
+            metainfo.info_hash_v1().as_deref(),
+            Some("fe1c385efcdb8e36c6c0991f5561325011baa2d0")
+        );
+        assert_eq!(
+            metainfo.info_hash_v2().as_deref(),
+            Some("b30100528910de686f510f99a051f402e6f74aed0a82d175a28a84b2031dbb76")
+        );
+        assert_eq!(*metainfo.hash(), "fe1c385efcdb8e36c6c0991f5561325011baa2d0");
+        // The padding file is left out.
+        let paths: Vec<&str> = metainfo.files().iter().map(|f| f.path().as_str()).collect();
+        assert_eq!(paths, ["dir/a", "dir/b"]);
+        assert_eq!(metainfo.total_size(), 16484);
+        assert_eq!(*metainfo.num_pieces(), 2);
+    }
+
+    #[test]
+    fn invalid() {
+        let negative = [
+            b"d6:lengthi-1e4:name1:a12:piece lengthi16384e6:pieces0:e".to_vec(),
+            b"d5:filesld6:lengthi-1e4:pathl1:aeee4:name1:a12:piece lengthi16384e6:pieces0:e"
+                .to_vec(),
+            b"d9:file treed1:ad0:d6:lengthi-1eeee12:meta versioni2e4:name1:a\
+                12:piece lengthi16384ee"
+                .to_vec(),
+        ];
+        for info in negative {
+            match Metainfo::from_bytes(&torrent(&info)) {
+                Err(Error::InvalidTorrent(msg)) => assert_eq!(msg, "invalid file length"),
+                other => panic!("{:?}", other),
+            }
+        }
+
+        let invalid = [
+            b"d4:name1:a12:piece lengthi16384e6:pieces0:e".to_vec(),
+            b"d6:lengthi1e4:name1:a12:piece lengthi0e6:pieces0:e".to_vec(),
+            b"d6:lengthi1e4:name1:a12:piece lengthi16384e6:pieces3:abce".to_vec(),
+            b"d6:lengthi1e4:name1:a12:piece lengthi16384ee".to_vec(),
+        ];
+        for info in invalid {
+            assert!(Metainfo::from_bytes(&torrent(&info)).is_err());
+        }
+        assert!(Metainfo::from_bytes(b"d4:infoi1ee").is_err());
+        assert!(Metainfo::from_bytes(b"not bencode").is_err());
+    }
+}
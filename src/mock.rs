@@ -0,0 +1,1397 @@
+//! In-process mock of the qbittorrent WebUI for offline tests.
+//!
+//! The server keeps an in-memory model of torrents, categories, tags, transfer
+//! limits, logs, RSS feeds and rules, search jobs and torrent creator tasks,
+//! and implements the login cookie flow. RSS feeds have no articles, and
+//! search jobs only find the results added with
+//! `MockServer::push_search_results`. Torrent creator tasks advance a step on
+//! every status request, and fail unless the source is below `/downloads/`.
+//! Requests to endpoints which are not implemented are answered with
+//! `404 Not Found`, and any endpoint can be answered with a fixed body with
+//! `MockServer::set_response`.
+//!
+//! ```no_run
+//! # async fn run() -> Result<(), Box<dyn std::error::Error>> {
+//! use qb_api::mock::{MockServer, MockTorrent};
+//!
+//! let server = MockServer::start().await?;
+//! server.add_torrent(MockTorrent::new("0123456789abcdef0123456789abcdef01234567", "debian"));
+//!
+//! let api = qb_api::Api::auth(&server.url(), "admin", "adminadmin").await?;
+//! assert_eq!(api.get_torrents().await?.len(), 1);
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::api::STOP_START_VERSION;
+use crate::data::{ApiVersion, LogLevel};
+use crate::magnet::Magnet;
+use crate::metainfo::Metainfo;
+use crate::rss::PATH_SEPARATOR;
+use http_body_util::{BodyExt, Full};
+use hyper::body::{Bytes, Incoming};
+use hyper::header::{CONTENT_TYPE, COOKIE, SET_COOKIE};
+use hyper::server::conn::http1;
+use hyper::service::service_fn;
+use hyper::{Request, Response, StatusCode};
+use hyper_util::rt::TokioIo;
+use serde_json::{json, Map, Value};
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::net::TcpListener;
+use tokio::task::JoinHandle;
+
+pub const USERNAME: &str = "admin";
+pub const PASSWORD: &str = "adminadmin";
+/// WebUI API version served unless changed with `set_api_version`
+pub const API_VERSION: ApiVersion = ApiVersion::new(2, 11, 2);
+/// Failed logins after which the client is banned, qbittorrent's default.
+pub const MAX_LOGIN_FAILURES: u32 = 5;
+
+/// A torrent in the mock's in-memory model.
+#[derive(Debug, Clone)]
+pub struct MockTorrent {
+    pub hash: String,
+    pub name: String,
+    pub category: String,
+    pub tags: BTreeSet<String>,
+    /// State as reported by the api, e.g. `downloading` or `stoppedUP`. Older
+    /// API versions report the stopped states as `pausedDL` and `pausedUP`.
+    pub state: String,
+    pub progress: f64,
+    pub size: i64,
+    pub save_path: String,
+    pub dlspeed: i64,
+    pub upspeed: i64,
+    pub dl_limit: i64,
+    pub up_limit: i64,
+    pub priority: i64,
+    pub added_on: i64,
+    pub magnet_uri: String,
+    pub tracker: String,
+    pub private: bool,
+}
+
+impl MockTorrent {
+    pub fn new(hash: impl Into<String>, name: impl Into<String>) -> Self {
+        let hash = hash.into();
+        Self {
+            magnet_uri: format!("magnet:?xt=urn:btih:{}", hash),
+            hash,
+            name: name.into(),
+            category: String::new(),
+            tags: BTreeSet::new(),
+            state: "downloading".into(),
+            progress: 0.0,
+            size: 0,
+            save_path: "/downloads".into(),
+            dlspeed: 0,
+            upspeed: 0,
+            dl_limit: 0,
+            up_limit: 0,
+            priority: 0,
+            added_on: now(),
+            tracker: String::new(),
+            private: false,
+        }
+    }
+
+    fn is_stopped(&self) -> bool {
+        self.state.starts_with("stopped") || self.state.starts_with("paused")
+    }
+
+    fn to_json(&self, stop_start: bool) -> Value {
+        let completed = (self.size as f64 * self.progress) as i64;
+        let tags: Vec<&str> = self.tags.iter().map(|t| t.as_str()).collect();
+        // Split in two, a single `json!` exceeds the macro recursion limit.
+        let mut value = json!({
+            "added_on": self.added_on,
+            "amount_left": self.size - completed,
+            "auto_tmm": false,
+            "category": self.category,
+            "completed": completed,
+            "completion_on": if self.progress >= 1.0 { self.added_on } else { -1 },
+            "dl_limit": self.dl_limit,
+            "dlspeed": self.dlspeed,
+            "downloaded": completed,
+            "downloaded_session": completed,
+            "eta": 8640000,
+            "f_l_piece_prio": false,
+            "force_start": false,
+            "hash": self.hash,
+            "last_activity": self.added_on,
+            "magnet_uri": self.magnet_uri,
+            "max_ratio": -1,
+            "max_seeding_time": -1,
+        });
+        let rest = json!({
+            "name": self.name,
+            "num_complete": 0,
+            "num_incomplete": 0,
+            "num_leechs": 0,
+            "num_seeds": 0,
+            "priority": self.priority,
+            "private": self.private,
+            "progress": self.progress,
+            "ratio": 0,
+            "ratio_limit": -2,
+            "save_path": self.save_path,
+            "seeding_time_limit": -2,
+            "seen_complete": -1,
+            "seq_dl": false,
+            "size": self.size,
+            "state": match self.state.strip_prefix("stopped") {
+                Some(state) if !stop_start => format!("paused{}", state),
+                _ => self.state.clone(),
+            },
+            "super_seeding": false,
+            "tags": tags.join(", "),
+            "time_active": 0,
+            "total_size": self.size,
+            "tracker": self.tracker,
+            "up_limit": self.up_limit,
+            "uploaded": 0,
+            "uploaded_session": 0,
+            "upspeed": self.upspeed,
+        });
+        if let (Value::Object(value), Value::Object(rest)) = (&mut value, rest) {
+            value.extend(rest);
+        }
+        value
+    }
+}
+
+#[derive(Debug, Clone)]
+struct MockLog {
+    id: u64,
+    message: String,
+    timestamp: i64,
+    level: LogLevel,
+}
+
+#[derive(Debug, Clone)]
+struct MockPeerLog {
+    id: u64,
+    ip: String,
+    timestamp: i64,
+    blocked: bool,
+    reason: String,
+}
+
+/// A node of the mock's RSS tree, feeds never have articles.
+#[derive(Debug, Clone)]
+enum MockRssItem {
+    Feed { uid: String, url: String },
+    Folder(BTreeMap<String, MockRssItem>),
+}
+
+impl MockRssItem {
+    fn to_json(&self, with_data: bool) -> Value {
+        match self {
+            MockRssItem::Feed { uid, url } if with_data => json!({
+                "uid": uid,
+                "url": url,
+                "title": "",
+                "lastBuildDate": "",
+                "isLoading": false,
+                "hasError": false,
+                "articles": [],
+            }),
+            MockRssItem::Feed { uid, url } => json!({ "uid": uid, "url": url }),
+            MockRssItem::Folder(items) => Value::Object(
+                items
+                    .iter()
+                    .map(|(name, item)| (name.clone(), item.to_json(with_data)))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct MockSearch {
+    results: Vec<Value>,
+    running: bool,
+}
+
+#[derive(Debug, Clone)]
+struct MockCreatorTask {
+    source_path: String,
+    private: bool,
+    /// `Queued`, `Running`, `Finished` or `Failed`
+    status: &'static str,
+}
+
+impl MockCreatorTask {
+    /// Sources outside of the default save path do not exist on the mock.
+    fn error_message(&self) -> Option<&'static str> {
+        (self.status == "Failed")
+            .then_some("Create new torrent file failed. Reason: no files in torrent.")
+    }
+
+    /// Take the next step, tasks for missing sources fail when they run.
+    fn advance(&mut self) {
+        self.status = match self.status {
+            "Queued" => "Running",
+            "Running" if self.source_path.starts_with("/downloads/") => "Finished",
+            "Running" => "Failed",
+            status => status,
+        };
+    }
+
+    /// A single file v1 .torrent named after the source.
+    fn torrent_file(&self) -> Vec<u8> {
+        let name = self.source_path.rsplit('/').next().unwrap_or_default();
+        let mut data = b"d4:infod6:lengthi1e".to_vec();
+        data.extend_from_slice(format!("4:name{}:{}", name.len(), name).as_bytes());
+        data.extend_from_slice(b"12:piece lengthi16384e6:pieces20:");
+        data.extend_from_slice(&[0; 20]);
+        if self.private {
+            data.extend_from_slice(b"7:privatei1e");
+        }
+        data.extend_from_slice(b"ee");
+        data
+    }
+}
+
+#[derive(Debug, Default)]
+struct MockState {
+    api_version: ApiVersion,
+    credentials: Option<(String, String)>,
+    sessions: HashSet<String>,
+    login_failures: u32,
+    next_sid: u64,
+    rid: u64,
+    /// Recently served maindata by rid, to answer with the changes since the
+    /// rid a client sends.
+    maindata: BTreeMap<u64, Value>,
+    maindata_requests: u64,
+    torrents: BTreeMap<String, MockTorrent>,
+    categories: BTreeMap<String, String>,
+    tags: BTreeSet<String>,
+    alt_speed_limits: bool,
+    dl_limit: i64,
+    up_limit: i64,
+    logs: Vec<MockLog>,
+    peer_logs: Vec<MockPeerLog>,
+    rss: BTreeMap<String, MockRssItem>,
+    next_feed_uid: u64,
+    /// Auto-download rules as sent to `rss/setRule`.
+    rss_rules: BTreeMap<String, Value>,
+    searches: BTreeMap<u64, MockSearch>,
+    creator_tasks: BTreeMap<String, MockCreatorTask>,
+    next_task_id: u64,
+    /// Fixed content types and bodies by endpoint, served instead of the model.
+    responses: HashMap<String, (String, Bytes)>,
+}
+
+/// A mock qbittorrent WebUI listening on a local port, shut down on drop.
+#[derive(Debug)]
+pub struct MockServer {
+    addr: SocketAddr,
+    state: Arc<Mutex<MockState>>,
+    handle: JoinHandle<()>,
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
+
+fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or_default()
+}
+
+impl MockServer {
+    /// Start a server which accepts the `admin`/`adminadmin` credentials as
+    /// well as logins without credentials, like a localhost bypass.
+    pub async fn start() -> std::io::Result<Self> {
+        Self::serve(MockState {
+            api_version: API_VERSION,
+            ..Default::default()
+        })
+        .await
+    }
+
+    /// Start a server which only accepts the given credentials.
+    pub async fn start_with_credentials(username: &str, password: &str) -> std::io::Result<Self> {
+        Self::serve(MockState {
+            api_version: API_VERSION,
+            credentials: Some((username.into(), password.into())),
+            ..Default::default()
+        })
+        .await
+    }
+
+    async fn serve(state: MockState) -> std::io::Result<Self> {
+        let listener = TcpListener::bind(("127.0.0.1", 0)).await?;
+        let addr = listener.local_addr()?;
+        let state = Arc::new(Mutex::new(state));
+
+        let server_state = state.clone();
+        let handle = tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let state = server_state.clone();
+                tokio::spawn(async move {
+                    let service = service_fn(move |request| serve_request(state.clone(), request));
+                    let io = TokioIo::new(stream);
+                    let _ = http1::Builder::new().serve_connection(io, service).await;
+                });
+            }
+        });
+
+        Ok(Self {
+            addr,
+            state,
+            handle,
+        })
+    }
+
+    fn state(&self) -> MutexGuard<'_, MockState> {
+        self.state.lock().unwrap()
+    }
+
+    /// Base url to pass to `Api::auth` or `Api::local`.
+    pub fn url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    /// Serve WebUI API `version`, this only affects the `Api` handles which
+    /// log in afterwards.
+    ///
+    /// Older versions use `torrents/pause` and `torrents/resume` and report
+    /// stopped torrents as paused. Endpoints are not otherwise restricted.
+    pub fn set_api_version(&self, version: ApiVersion) {
+        self.state().api_version = version;
+    }
+
+    /// Invalidate all session cookies, as if the WebUI session timed out.
+    pub fn expire_sessions(&self) {
+        self.state().sessions.clear();
+    }
+
+    /// Add or replace a torrent, the hash is stored in lowercase like
+    /// qbittorrent does.
+    pub fn add_torrent(&self, mut torrent: MockTorrent) {
+        let mut state = self.state();
+        state.rid += 1;
+        torrent.hash.make_ascii_lowercase();
+        state.torrents.insert(torrent.hash.clone(), torrent);
+    }
+
+    /// Modify the torrent `hash`, returns false if it does not exist.
+    pub fn update_torrent(&self, hash: &str, f: impl FnOnce(&mut MockTorrent)) -> bool {
+        let mut state = self.state();
+        state.rid += 1;
+        let hash = hash.to_ascii_lowercase();
+        state.torrents.get_mut(&hash).map(f).is_some()
+    }
+
+    pub fn remove_torrent(&self, hash: &str) -> Option<MockTorrent> {
+        let mut state = self.state();
+        state.rid += 1;
+        state.torrents.remove(&hash.to_ascii_lowercase())
+    }
+
+    pub fn torrents(&self) -> Vec<MockTorrent> {
+        self.state().torrents.values().cloned().collect()
+    }
+
+    /// Categories and their save paths.
+    pub fn categories(&self) -> BTreeMap<String, String> {
+        self.state().categories.clone()
+    }
+
+    pub fn tags(&self) -> BTreeSet<String> {
+        self.state().tags.clone()
+    }
+
+    /// Number of `sync/maindata` requests served so far.
+    pub fn maindata_requests(&self) -> u64 {
+        self.state().maindata_requests
+    }
+
+    /// Add results named `names` to the search job `id`, as if its plugins
+    /// found them. Returns false if the job does not exist.
+    pub fn push_search_results(&self, id: u64, names: &[&str]) -> bool {
+        let mut state = self.state();
+        let Some(search) = state.searches.get_mut(&id) else {
+            return false;
+        };
+        for name in names {
+            search.results.push(json!({
+                "descrLink": format!("https://example.org/{}", name),
+                "fileName": name,
+                "fileSize": -1,
+                "fileUrl": format!("https://example.org/{}.torrent", name),
+                "nbLeechers": 0,
+                "nbSeeders": 0,
+                "siteUrl": "https://example.org",
+                "engineName": "mock",
+                "pubDate": now(),
+            }));
+        }
+        true
+    }
+
+    /// Append an entry to the main log.
+    pub fn push_log(&self, level: LogLevel, message: &str) {
+        self.state().log(level, message.into());
+    }
+
+    /// Answer every request to `endpoint`, e.g. `torrents/info`, with `body`
+    /// instead of the model, to replay recorded responses. The session is
+    /// still checked.
+    pub fn set_response(&self, endpoint: &str, content_type: &str, body: impl Into<Bytes>) {
+        self.state()
+            .responses
+            .insert(endpoint.into(), (content_type.into(), body.into()));
+    }
+}
+
+type Form = HashMap<String, String>;
+
+/// Number of served maindata responses which clients can request changes to.
+const MAINDATA_HISTORY: usize = 16;
+
+/// The changes from `old` to `new` maindata in the form of a partial update,
+/// leaving out empty fields like qbittorrent.
+fn maindata_changes(old: &Value, new: &Value) -> Map<String, Value> {
+    let mut changes = Map::new();
+    for key in ["torrents", "categories", "server_state"] {
+        let empty = Map::new();
+        let old = old[key].as_object().unwrap_or(&empty);
+        let new = new[key].as_object().unwrap_or(&empty);
+        let (changed, removed) = object_changes(old, new);
+        if !changed.is_empty() {
+            changes.insert(key.into(), changed.into());
+        }
+        if !removed.is_empty() && key != "server_state" {
+            changes.insert(format!("{}_removed", key), removed.into());
+        }
+    }
+
+    let tags = |data: &Value| -> BTreeSet<String> {
+        serde_json::from_value(data["tags"].clone()).unwrap_or_default()
+    };
+    let (old_tags, new_tags) = (tags(old), tags(new));
+    let added: Vec<&String> = new_tags.difference(&old_tags).collect();
+    let removed: Vec<&String> = old_tags.difference(&new_tags).collect();
+    if !added.is_empty() {
+        changes.insert("tags".into(), json!(added));
+    }
+    if !removed.is_empty() {
+        changes.insert("tags_removed".into(), json!(removed));
+    }
+    changes
+}
+
+/// The added or changed entries of `new` and the keys missing from it. The
+/// entries which are objects themselves only contain the changed fields.
+fn object_changes(
+    old: &Map<String, Value>,
+    new: &Map<String, Value>,
+) -> (Map<String, Value>, Vec<String>) {
+    let mut changed = Map::new();
+    for (key, value) in new {
+        match (old.get(key), value) {
+            (Some(Value::Object(old)), Value::Object(new)) => {
+                let (fields, _) = object_changes(old, new);
+                if !fields.is_empty() {
+                    changed.insert(key.clone(), fields.into());
+                }
+            }
+            (Some(old), new) if old == new => {}
+            _ => {
+                changed.insert(key.clone(), value.clone());
+            }
+        }
+    }
+    let removed = old
+        .keys()
+        .filter(|key| !new.contains_key(*key))
+        .cloned()
+        .collect();
+    (changed, removed)
+}
+
+/// A file uploaded in a `multipart/form-data` request.
+struct Upload {
+    field: String,
+    data: Vec<u8>,
+}
+
+fn response(status: StatusCode, body: impl Into<Bytes>) -> Response<Full<Bytes>> {
+    let mut response = Response::new(Full::new(body.into()));
+    *response.status_mut() = status;
+    response
+}
+
+fn ok(body: impl Into<Bytes>) -> Response<Full<Bytes>> {
+    response(StatusCode::OK, body)
+}
+
+fn json_response(value: Value) -> Response<Full<Bytes>> {
+    let mut response = ok(value.to_string());
+    response
+        .headers_mut()
+        .insert(CONTENT_TYPE, "application/json".parse().unwrap());
+    response
+}
+
+fn flag(form: &Form, key: &str, default: bool) -> bool {
+    form.get(key).map(|v| v == "true").unwrap_or(default)
+}
+
+fn split(value: Option<&String>, separator: char) -> impl Iterator<Item = &str> {
+    value
+        .into_iter()
+        .flat_map(move |v| v.split(separator))
+        .map(|v| v.trim())
+        .filter(|v| !v.is_empty())
+}
+
+/// Parse a `multipart/form-data` body into its text fields and files.
+///
+/// This only covers what `Api` sends, not all of RFC 7578: the boundary may
+/// be quoted but must not contain escaped characters, a part is a file if its
+/// `Content-Disposition` has a `filename`, and the `Content-Type` and other
+/// headers of the parts are ignored. Text fields are decoded as lossy UTF-8.
+fn parse_multipart(content_type: &str, body: &[u8]) -> (Form, Vec<Upload>) {
+    let mut form = Form::new();
+    let mut uploads = Vec::new();
+    let boundary = content_type
+        .split(';')
+        .filter_map(|param| param.trim().strip_prefix("boundary="))
+        .next();
+    let boundary = match boundary {
+        Some(boundary) => format!("--{}", boundary.trim_matches('"')),
+        None => return (form, uploads),
+    };
+
+    let find = |data: &[u8], needle: &[u8]| data.windows(needle.len()).position(|w| w == needle);
+    let mut rest = body;
+    while let Some(start) = find(rest, boundary.as_bytes()) {
+        rest = &rest[start + boundary.len()..];
+        let end = find(rest, boundary.as_bytes()).unwrap_or(rest.len());
+        let part = &rest[..end];
+        let Some(split) = find(part, b"\r\n\r\n") else {
+            continue;
+        };
+        let headers = String::from_utf8_lossy(&part[..split]);
+        let data = part[split + 4..]
+            .strip_suffix(b"\r\n")
+            .unwrap_or(&part[split + 4..]);
+
+        let disposition = headers
+            .lines()
+            .find(|l| l.to_ascii_lowercase().starts_with("content-disposition"))
+            .unwrap_or_default();
+        let param = |key: &str| {
+            disposition
+                .split(';')
+                .filter_map(|p| p.trim().strip_prefix(key))
+                .map(|v| v.trim_matches('"').to_string())
+                .next()
+        };
+        let Some(name) = param("name=") else {
+            continue;
+        };
+        if param("filename=").is_some() {
+            uploads.push(Upload {
+                field: name,
+                data: data.to_vec(),
+            });
+        } else {
+            form.insert(name, String::from_utf8_lossy(data).into_owned());
+        }
+    }
+    (form, uploads)
+}
+
+async fn serve_request(
+    state: Arc<Mutex<MockState>>,
+    request: Request<Incoming>,
+) -> Result<Response<Full<Bytes>>, Infallible> {
+    let (parts, body) = request.into_parts();
+    let body = match body.collect().await {
+        Ok(body) => body.to_bytes(),
+        Err(_) => return Ok(response(StatusCode::BAD_REQUEST, "")),
+    };
+
+    let content_type = parts
+        .headers
+        .get(CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default();
+    let (mut form, uploads) = if content_type.starts_with("multipart/form-data") {
+        parse_multipart(content_type, &body)
+    } else {
+        let form = url::form_urlencoded::parse(&body).into_owned().collect();
+        (form, Vec::new())
+    };
+    if let Some(query) = parts.uri.query() {
+        form.extend(url::form_urlencoded::parse(query.as_bytes()).into_owned());
+    }
+
+    let sid = parts
+        .headers
+        .get_all(COOKIE)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(';'))
+        .filter_map(|c| c.trim().strip_prefix("SID="))
+        .map(|sid| sid.to_string())
+        .next();
+
+    let mut state = state.lock().unwrap();
+    let path = parts.uri.path();
+    if path == "/api/v2/auth/login" {
+        return Ok(state.login(&form));
+    }
+    if !sid.is_some_and(|sid| state.sessions.contains(&sid)) {
+        return Ok(response(StatusCode::FORBIDDEN, "Forbidden"));
+    }
+    Ok(state.handle(path, &form, &uploads))
+}
+
+impl MockState {
+    fn login(&mut self, form: &Form) -> Response<Full<Bytes>> {
+        let username = form.get("username").map(|s| s.as_str());
+        let password = form.get("password").map(|s| s.as_str());
+        let accepted = match &self.credentials {
+            Some((u, p)) => username == Some(u.as_str()) && password == Some(p.as_str()),
+            None => {
+                (username.is_none() && password.is_none())
+                    || (username == Some(USERNAME) && password == Some(PASSWORD))
+            }
+        };
+        if self.login_failures >= MAX_LOGIN_FAILURES {
+            return response(
+                StatusCode::FORBIDDEN,
+                "Your IP address has been banned after too many failed authentication attempts.",
+            );
+        }
+        if !accepted {
+            self.login_failures += 1;
+            return ok("Fails.");
+        }
+
+        self.next_sid += 1;
+        let sid = format!("mock{}", self.next_sid);
+        self.sessions.insert(sid.clone());
+        let mut response = ok("Ok.");
+        let cookie = format!("SID={}; HttpOnly; path=/", sid);
+        response
+            .headers_mut()
+            .insert(SET_COOKIE, cookie.parse().unwrap());
+        response
+    }
+
+    fn log(&mut self, level: LogLevel, message: String) {
+        let id = self.logs.len() as u64;
+        self.logs.push(MockLog {
+            id,
+            message,
+            timestamp: now(),
+            level,
+        });
+    }
+
+    /// Whether the served version has the qbittorrent 5.0 names.
+    fn stop_start(&self) -> bool {
+        self.api_version >= STOP_START_VERSION
+    }
+
+    fn handle(&mut self, path: &str, form: &Form, uploads: &[Upload]) -> Response<Full<Bytes>> {
+        let stop_start = self.stop_start();
+        let endpoint = path.strip_prefix("/api/v2/").unwrap_or(path);
+        if let Some((content_type, body)) = self.responses.get(endpoint) {
+            let mut response = ok(body.clone());
+            response
+                .headers_mut()
+                .insert(CONTENT_TYPE, content_type.parse().unwrap());
+            return response;
+        }
+        match endpoint {
+            "app/version" => ok(if stop_start { "v5.0.0" } else { "v4.6.7" }),
+            "app/webapiVersion" => ok(self.api_version.to_string()),
+            "app/buildInfo" => json_response(json!({
+                "qt": "6.7.2",
+                "libtorrent": "2.0.10.0",
+                "boost": "1.85.0",
+                "openssl": "3.3.1",
+                "bitness": 64,
+            })),
+            "app/defaultSavePath" => ok("/downloads"),
+
+            "log/main" => self.main_logs(form),
+            "log/peers" => self.peer_logs(form),
+
+            "sync/maindata" => self.maindata(form),
+
+            "transfer/info" => json_response(self.transfer_info()),
+            "transfer/speedLimitsMode" => ok(if self.alt_speed_limits { "1" } else { "0" }),
+            "transfer/toggleSpeedLimitsMode" => {
+                self.alt_speed_limits = !self.alt_speed_limits;
+                ok("")
+            }
+            "transfer/downloadLimit" => ok(self.dl_limit.to_string()),
+            "transfer/uploadLimit" => ok(self.up_limit.to_string()),
+            "transfer/setDownloadLimit" | "transfer/setUploadLimit" => {
+                let Some(limit) = form.get("limit").and_then(|l| l.parse().ok()) else {
+                    return response(StatusCode::BAD_REQUEST, "");
+                };
+                if endpoint == "transfer/setDownloadLimit" {
+                    self.dl_limit = limit;
+                } else {
+                    self.up_limit = limit;
+                }
+                ok("")
+            }
+            "transfer/banPeers" => {
+                for peer in split(form.get("peers"), '|') {
+                    let id = self.peer_logs.len() as u64;
+                    self.peer_logs.push(MockPeerLog {
+                        id,
+                        ip: peer
+                            .rsplit_once(':')
+                            .map(|(ip, _)| ip)
+                            .unwrap_or(peer)
+                            .into(),
+                        timestamp: now(),
+                        blocked: true,
+                        reason: "Manually banned".into(),
+                    });
+                }
+                ok("")
+            }
+
+            "torrents/info" => self.torrents_info(form),
+            "torrents/add" => self.add(form, uploads),
+            "torrents/delete" => {
+                let hashes = self.hashes(form);
+                for hash in hashes {
+                    self.torrents.remove(&hash);
+                }
+                self.rid += 1;
+                ok("")
+            }
+            "torrents/stop" | "torrents/start" | "torrents/pause" | "torrents/resume" => {
+                let renamed = matches!(endpoint, "torrents/stop" | "torrents/start");
+                if renamed != stop_start {
+                    return response(StatusCode::NOT_FOUND, "Not Found");
+                }
+                let stop = matches!(endpoint, "torrents/stop" | "torrents/pause");
+                for hash in self.hashes(form) {
+                    if let Some(torrent) = self.torrents.get_mut(&hash) {
+                        let done = torrent.progress >= 1.0;
+                        torrent.state = match (stop, done) {
+                            (true, true) => "stoppedUP",
+                            (true, false) => "stoppedDL",
+                            (false, true) => "uploading",
+                            (false, false) => "downloading",
+                        }
+                        .into();
+                    }
+                }
+                self.rid += 1;
+                ok("")
+            }
+            "torrents/setCategory" => {
+                let category = form.get("category").cloned().unwrap_or_default();
+                if !category.is_empty() && !self.categories.contains_key(&category) {
+                    return response(StatusCode::CONFLICT, "Category does not exist");
+                }
+                for hash in self.hashes(form) {
+                    if let Some(torrent) = self.torrents.get_mut(&hash) {
+                        torrent.category = category.clone();
+                    }
+                }
+                self.rid += 1;
+                ok("")
+            }
+            "torrents/addTags" | "torrents/removeTags" => {
+                let add = endpoint == "torrents/addTags";
+                let tags: Vec<String> = split(form.get("tags"), ',').map(String::from).collect();
+                if add {
+                    self.tags.extend(tags.iter().cloned());
+                }
+                for hash in self.hashes(form) {
+                    if let Some(torrent) = self.torrents.get_mut(&hash) {
+                        for tag in &tags {
+                            if add {
+                                torrent.tags.insert(tag.clone());
+                            } else {
+                                torrent.tags.remove(tag);
+                            }
+                        }
+                    }
+                }
+                self.rid += 1;
+                ok("")
+            }
+
+            "torrents/categories" => json_response(self.categories_json()),
+            "torrents/createCategory" | "torrents/editCategory" => {
+                let name = form.get("category").cloned().unwrap_or_default();
+                let path = form.get("savePath").cloned().unwrap_or_default();
+                if name.is_empty() {
+                    return response(StatusCode::BAD_REQUEST, "Category name cannot be empty");
+                }
+                let exists = self.categories.contains_key(&name);
+                if endpoint == "torrents/createCategory" && exists {
+                    return response(StatusCode::CONFLICT, "Unable to create category");
+                }
+                if endpoint == "torrents/editCategory" && !exists {
+                    return response(StatusCode::CONFLICT, "Unable to edit category");
+                }
+                self.categories.insert(name, path);
+                self.rid += 1;
+                ok("")
+            }
+            "torrents/removeCategories" => {
+                for name in split(form.get("categories"), '\n') {
+                    self.categories.remove(name);
+                    for torrent in self.torrents.values_mut() {
+                        if torrent.category == name {
+                            torrent.category.clear();
+                        }
+                    }
+                }
+                self.rid += 1;
+                ok("")
+            }
+
+            "torrents/tags" => json_response(json!(self.tags)),
+            "torrents/createTags" => {
+                let tags: Vec<String> = split(form.get("tags"), ',').map(String::from).collect();
+                self.tags.extend(tags);
+                self.rid += 1;
+                ok("")
+            }
+            "torrents/deleteTags" => {
+                for tag in split(form.get("tags"), ',') {
+                    self.tags.remove(tag);
+                    for torrent in self.torrents.values_mut() {
+                        torrent.tags.remove(tag);
+                    }
+                }
+                self.rid += 1;
+                ok("")
+            }
+
+            "rss/items" => {
+                let root = MockRssItem::Folder(self.rss.clone());
+                json_response(root.to_json(flag(form, "withData", false)))
+            }
+            "rss/addFolder" | "rss/addFeed" => {
+                let path = form.get("path").cloned().unwrap_or_default();
+                let item = if endpoint == "rss/addFolder" {
+                    MockRssItem::Folder(BTreeMap::new())
+                } else {
+                    let Some(url) = form.get("url") else {
+                        return response(StatusCode::BAD_REQUEST, "");
+                    };
+                    self.next_feed_uid += 1;
+                    MockRssItem::Feed {
+                        uid: format!("{{00000000-0000-0000-0000-{:012x}}}", self.next_feed_uid),
+                        url: url.clone(),
+                    }
+                };
+                self.rss_insert(&path, item)
+            }
+            "rss/removeItem" => {
+                let path = form.get("path").cloned().unwrap_or_default();
+                match self.rss_remove(&path) {
+                    Some(_) => ok(""),
+                    None => response(StatusCode::CONFLICT, "Item doesn't exist"),
+                }
+            }
+            "rss/moveItem" => {
+                let item_path = form.get("itemPath").cloned().unwrap_or_default();
+                let dest_path = form.get("destPath").cloned().unwrap_or_default();
+                let Some(item) = self.rss_remove(&item_path) else {
+                    return response(StatusCode::CONFLICT, "Item doesn't exist");
+                };
+                let moved = self.rss_insert(&dest_path, item.clone());
+                if moved.status() != StatusCode::OK {
+                    self.rss_insert(&item_path, item);
+                }
+                moved
+            }
+
+            "rss/rules" => json_response(json!(self.rss_rules)),
+            "rss/setRule" => {
+                let name = form.get("ruleName").cloned().unwrap_or_default();
+                let def = form
+                    .get("ruleDef")
+                    .and_then(|d| serde_json::from_str(d).ok());
+                let Some(def) = def.filter(|_: &Value| !name.is_empty()) else {
+                    return response(StatusCode::BAD_REQUEST, "");
+                };
+                self.rss_rules.insert(name, def);
+                ok("")
+            }
+            "rss/renameRule" => {
+                let name = form.get("ruleName").cloned().unwrap_or_default();
+                let new_name = form.get("newRuleName").cloned().unwrap_or_default();
+                if let Some(rule) = self.rss_rules.remove(&name) {
+                    self.rss_rules.insert(new_name, rule);
+                }
+                ok("")
+            }
+            "rss/removeRule" => {
+                let name = form.get("ruleName").cloned().unwrap_or_default();
+                self.rss_rules.remove(&name);
+                ok("")
+            }
+            "rss/matchingArticles" => json_response(json!({})),
+
+            "search/start" => {
+                let pattern = form.get("pattern").cloned().unwrap_or_default();
+                if pattern.is_empty() {
+                    return response(StatusCode::BAD_REQUEST, "");
+                }
+                let id = self.searches.keys().next_back().map_or(0, |id| id + 1);
+                let search = MockSearch {
+                    results: Vec::new(),
+                    running: true,
+                };
+                self.searches.insert(id, search);
+                json_response(json!({ "id": id }))
+            }
+            "search/status" => {
+                let id: Option<u64> = form.get("id").and_then(|id| id.parse().ok());
+                if id.is_some_and(|id| !self.searches.contains_key(&id)) {
+                    return response(StatusCode::NOT_FOUND, "Not Found");
+                }
+                let status: Vec<Value> = self
+                    .searches
+                    .iter()
+                    .filter(|(job, _)| id.is_none() || id == Some(**job))
+                    .map(|(id, search)| {
+                        json!({
+                            "id": id,
+                            "status": if search.running { "Running" } else { "Stopped" },
+                            "total": search.results.len(),
+                        })
+                    })
+                    .collect();
+                json_response(Value::Array(status))
+            }
+            "search/results" => {
+                let Some(search) = self.search_id(form).map(|id| &self.searches[&id]) else {
+                    return response(StatusCode::NOT_FOUND, "Not Found");
+                };
+                let total = search.results.len();
+                let offset: usize = form.get("offset").and_then(|o| o.parse().ok()).unwrap_or(0);
+                if offset > total {
+                    return response(StatusCode::CONFLICT, "Offset is out of range");
+                }
+                let limit: usize = form
+                    .get("limit")
+                    .and_then(|l| l.parse().ok())
+                    .filter(|l| *l > 0)
+                    .unwrap_or(usize::MAX);
+                let results: Vec<&Value> = search.results.iter().skip(offset).take(limit).collect();
+                json_response(json!({
+                    "results": results,
+                    "status": if search.running { "Running" } else { "Stopped" },
+                    "total": total,
+                }))
+            }
+            "search/stop" | "search/delete" => {
+                let Some(id) = self.search_id(form) else {
+                    return response(StatusCode::NOT_FOUND, "Not Found");
+                };
+                if endpoint == "search/stop" {
+                    self.searches.get_mut(&id).unwrap().running = false;
+                } else {
+                    self.searches.remove(&id);
+                }
+                ok("")
+            }
+
+            "torrentcreator/addTask" => {
+                let Some(source_path) = form.get("sourcePath").filter(|p| !p.is_empty()) else {
+                    return response(StatusCode::BAD_REQUEST, "");
+                };
+                self.next_task_id += 1;
+                let id = format!("mock-task-{}", self.next_task_id);
+                let task = MockCreatorTask {
+                    source_path: source_path.clone(),
+                    private: flag(form, "private", false),
+                    status: "Queued",
+                };
+                self.creator_tasks.insert(id.clone(), task);
+                json_response(json!({ "taskID": id }))
+            }
+            "torrentcreator/status" => {
+                let id = form.get("taskID");
+                if id.is_some_and(|id| !self.creator_tasks.contains_key(id)) {
+                    return response(StatusCode::NOT_FOUND, "Not Found");
+                }
+                let status: Vec<Value> = self
+                    .creator_tasks
+                    .iter_mut()
+                    .filter(|(task_id, _)| id.is_none() || id == Some(*task_id))
+                    .map(|(task_id, task)| {
+                        task.advance();
+                        json!({
+                            "taskID": task_id,
+                            "sourcePath": task.source_path,
+                            "status": task.status,
+                            "errorMessage": task.error_message(),
+                            "private": task.private,
+                        })
+                    })
+                    .collect();
+                json_response(Value::Array(status))
+            }
+            "torrentcreator/torrentFile" => {
+                let task = form.get("taskID").and_then(|id| self.creator_tasks.get(id));
+                match task {
+                    None => response(StatusCode::NOT_FOUND, "Not Found"),
+                    Some(task) if task.status == "Finished" => {
+                        let mut response = ok(task.torrent_file());
+                        response
+                            .headers_mut()
+                            .insert(CONTENT_TYPE, "application/x-bittorrent".parse().unwrap());
+                        response
+                    }
+                    Some(_) => response(StatusCode::CONFLICT, "Torrent creation is not finished"),
+                }
+            }
+            "torrentcreator/deleteTask" => {
+                let task = form
+                    .get("taskID")
+                    .and_then(|id| self.creator_tasks.remove(id));
+                match task {
+                    Some(_) => ok(""),
+                    None => response(StatusCode::NOT_FOUND, "Not Found"),
+                }
+            }
+
+            _ => response(StatusCode::NOT_FOUND, "Not Found"),
+        }
+    }
+
+    /// The `id` in `form` if it is a known search job.
+    fn search_id(&self, form: &Form) -> Option<u64> {
+        let id = form.get("id")?.parse().ok()?;
+        self.searches.contains_key(&id).then_some(id)
+    }
+
+    /// The folder at the RSS item path `path`, the root if it is empty.
+    fn rss_folder(&mut self, path: &[&str]) -> Option<&mut BTreeMap<String, MockRssItem>> {
+        let mut folder = &mut self.rss;
+        for name in path {
+            match folder.get_mut(*name) {
+                Some(MockRssItem::Folder(items)) => folder = items,
+                _ => return None,
+            }
+        }
+        Some(folder)
+    }
+
+    /// Add `item` at `path`, its parent folder must exist.
+    fn rss_insert(&mut self, path: &str, item: MockRssItem) -> Response<Full<Bytes>> {
+        let mut names: Vec<&str> = path.split(PATH_SEPARATOR).collect();
+        let name = names.pop().unwrap_or_default();
+        match self.rss_folder(&names) {
+            Some(folder) if !name.is_empty() && !folder.contains_key(name) => {
+                folder.insert(name.into(), item);
+                ok("")
+            }
+            _ => response(StatusCode::CONFLICT, "Couldn't add item"),
+        }
+    }
+
+    fn rss_remove(&mut self, path: &str) -> Option<MockRssItem> {
+        let mut names: Vec<&str> = path.split(PATH_SEPARATOR).collect();
+        let name = names.pop().unwrap_or_default();
+        self.rss_folder(&names)?.remove(name)
+    }
+
+    /// Hashes of the torrents selected by the `hashes` parameter.
+    fn hashes(&self, form: &Form) -> Vec<String> {
+        match form.get("hashes").map(|h| h.as_str()) {
+            Some("all") => self.torrents.keys().cloned().collect(),
+            _ => split(form.get("hashes"), '|')
+                .map(|h| h.to_ascii_lowercase())
+                .collect(),
+        }
+    }
+
+    fn categories_json(&self) -> Value {
+        let categories: Map<String, Value> = self
+            .categories
+            .iter()
+            .map(|(name, path)| (name.clone(), json!({ "name": name, "savePath": path })))
+            .collect();
+        Value::Object(categories)
+    }
+
+    fn transfer_info(&self) -> Value {
+        let dl_speed: i64 = self.torrents.values().map(|t| t.dlspeed).sum();
+        let up_speed: i64 = self.torrents.values().map(|t| t.upspeed).sum();
+        json!({
+            "dl_info_speed": dl_speed,
+            "dl_info_data": 0,
+            "up_info_speed": up_speed,
+            "up_info_data": 0,
+            "dl_rate_limit": self.dl_limit,
+            "up_rate_limit": self.up_limit,
+            "dht_nodes": 0,
+            "connection_status": "connected",
+        })
+    }
+
+    /// The changes since the `rid` in `form` if it was served recently,
+    /// otherwise a full update.
+    fn maindata(&mut self, form: &Form) -> Response<Full<Bytes>> {
+        self.maindata_requests += 1;
+        let torrents: Map<String, Value> = self
+            .torrents
+            .values()
+            .map(|t| (t.hash.clone(), t.to_json(self.stop_start())))
+            .collect();
+        let mut server_state = self.transfer_info();
+        let extra = json!({
+            "alltime_dl": 0,
+            "alltime_ul": 0,
+            "free_space_on_disk": 1_000_000_000_000i64,
+            "global_ratio": "0.00",
+            "queueing": false,
+            "refresh_interval": 1500,
+            "use_alt_speed_limits": self.alt_speed_limits,
+        });
+        if let (Value::Object(state), Value::Object(extra)) = (&mut server_state, extra) {
+            state.extend(extra);
+        }
+        let data = json!({
+            "torrents": torrents,
+            "categories": self.categories_json(),
+            "tags": self.tags,
+            "server_state": server_state,
+        });
+
+        let rid: u64 = form.get("rid").and_then(|r| r.parse().ok()).unwrap_or(0);
+        let mut response = match self.maindata.get(&rid) {
+            Some(old) => maindata_changes(old, &data),
+            None => {
+                let mut full = data.as_object().cloned().unwrap_or_default();
+                full.insert("full_update".into(), true.into());
+                full
+            }
+        };
+        self.rid += 1;
+        response.insert("rid".into(), self.rid.into());
+        self.maindata.insert(self.rid, data);
+        while self.maindata.len() > MAINDATA_HISTORY {
+            self.maindata.pop_first();
+        }
+        json_response(Value::Object(response))
+    }
+
+    fn torrents_info(&self, form: &Form) -> Response<Full<Bytes>> {
+        let hashes: HashSet<String> = self.hashes(form).into_iter().collect();
+        let filter = form.get("filter").map(|f| f.as_str()).unwrap_or("all");
+        let stop_start = self.stop_start();
+        let mut torrents: Vec<&MockTorrent> = self
+            .torrents
+            .values()
+            .filter(|t| hashes.is_empty() || hashes.contains(&t.hash))
+            .filter(|t| match form.get("category") {
+                Some(category) => &t.category == category,
+                None => true,
+            })
+            .filter(|t| match form.get("tag") {
+                Some(tag) => t.tags.contains(tag),
+                None => true,
+            })
+            .filter(|t| match form.get("private").map(|p| p.as_str()) {
+                Some(private) => t.private == (private == "true"),
+                None => true,
+            })
+            .filter(|t| {
+                let active = t.dlspeed > 0 || t.upspeed > 0;
+                match filter {
+                    "downloading" => !t.is_stopped() && t.progress < 1.0,
+                    "seeding" => !t.is_stopped() && t.progress >= 1.0,
+                    "completed" => t.progress >= 1.0,
+                    "stopped" if stop_start => t.is_stopped(),
+                    "running" if stop_start => !t.is_stopped(),
+                    "paused" if !stop_start => t.is_stopped(),
+                    "resumed" if !stop_start => !t.is_stopped(),
+                    "active" => active,
+                    "inactive" => !active,
+                    "stalled" => t.state.starts_with("stalled"),
+                    "stalled_uploading" => t.state == "stalledUP",
+                    "stalled_downloading" => t.state == "stalledDL",
+                    "errored" => t.state == "error" || t.state == "missingFiles",
+                    _ => true,
+                }
+            })
+            .collect();
+
+        let mut values: Vec<Value> = torrents.drain(..).map(|t| t.to_json(stop_start)).collect();
+        if let Some(sort) = form.get("sort") {
+            values.sort_by(|a, b| compare(&a[sort.as_str()], &b[sort.as_str()]));
+        }
+        if flag(form, "reverse", false) {
+            values.reverse();
+        }
+        let offset: i64 = form.get("offset").and_then(|o| o.parse().ok()).unwrap_or(0);
+        let offset = if offset < 0 {
+            values.len().saturating_sub(offset.unsigned_abs() as usize)
+        } else {
+            offset as usize
+        };
+        let limit: usize = form
+            .get("limit")
+            .and_then(|l| l.parse().ok())
+            .filter(|l| *l > 0)
+            .unwrap_or(usize::MAX);
+        let values: Vec<Value> = values.into_iter().skip(offset).take(limit).collect();
+        json_response(Value::Array(values))
+    }
+
+    fn add(&mut self, form: &Form, uploads: &[Upload]) -> Response<Full<Bytes>> {
+        let stopped = flag(
+            form,
+            if self.stop_start() {
+                "stopped"
+            } else {
+                "paused"
+            },
+            false,
+        );
+        let mut added = Vec::new();
+
+        for url in split(form.get("urls"), '\n') {
+            let Ok(magnet) = url.parse::<Magnet>() else {
+                continue;
+            };
+            let hash = magnet.hash().to_string();
+            let name = magnet
+                .display_name()
+                .clone()
+                .unwrap_or_else(|| hash.clone());
+            let mut torrent = MockTorrent::new(hash, name);
+            torrent.magnet_uri = url.into();
+            torrent.size = magnet.exact_length().unwrap_or_default() as i64;
+            torrent.state = if stopped { "stoppedDL" } else { "metaDL" }.into();
+            added.push(torrent);
+        }
+        for upload in uploads.iter().filter(|u| u.field == "torrents") {
+            let Ok(metainfo) = Metainfo::from_bytes(&upload.data) else {
+                return response(StatusCode::UNSUPPORTED_MEDIA_TYPE, "");
+            };
+            let mut torrent = MockTorrent::new(metainfo.hash().to_string(), metainfo.name());
+            torrent.size = metainfo.total_size() as i64;
+            torrent.private = *metainfo.private();
+            torrent.tracker = metainfo
+                .trackers()
+                .first()
+                .and_then(|tier| tier.first())
+                .cloned()
+                .unwrap_or_default();
+            torrent.state = if stopped { "stoppedDL" } else { "downloading" }.into();
+            added.push(torrent);
+        }
+
+        let mut count = 0;
+        for mut torrent in added {
+            if self.torrents.contains_key(&torrent.hash) {
+                continue;
+            }
+            if let Some(category) = form.get("category") {
+                torrent.category = category.clone();
+            }
+            if let Some(path) = form.get("savepath") {
+                torrent.save_path = path.clone();
+            }
+            torrent.tags = split(form.get("tags"), ',').map(String::from).collect();
+            self.tags.extend(torrent.tags.iter().cloned());
+            self.log(
+                LogLevel::Normal,
+                format!("Added new torrent. Torrent: \"{}\"", torrent.name),
+            );
+            self.torrents.insert(torrent.hash.clone(), torrent);
+            count += 1;
+        }
+        self.rid += 1;
+        ok(if count > 0 { "Ok." } else { "Fails." })
+    }
+
+    fn main_logs(&self, form: &Form) -> Response<Full<Bytes>> {
+        let last_known_id: i64 = form
+            .get("last_known_id")
+            .and_then(|id| id.parse().ok())
+            .unwrap_or(-1);
+        let logs: Vec<Value> = self
+            .logs
+            .iter()
+            .filter(|l| l.id as i64 > last_known_id)
+            .filter(|l| match l.level {
+                LogLevel::Normal => flag(form, "normal", true),
+                LogLevel::Info => flag(form, "info", true),
+                LogLevel::Warning => flag(form, "warning", true),
+                LogLevel::Critical => flag(form, "critical", true),
+            })
+            .map(|l| {
+                json!({
+                    "id": l.id,
+                    "message": l.message,
+                    "timestamp": l.timestamp,
+                    "type": u64::from(l.level),
+                })
+            })
+            .collect();
+        json_response(Value::Array(logs))
+    }
+
+    fn peer_logs(&self, form: &Form) -> Response<Full<Bytes>> {
+        let last_known_id: i64 = form
+            .get("last_known_id")
+            .and_then(|id| id.parse().ok())
+            .unwrap_or(-1);
+        let logs: Vec<Value> = self
+            .peer_logs
+            .iter()
+            .filter(|l| l.id as i64 > last_known_id)
+            .map(|l| {
+                json!({
+                    "id": l.id,
+                    "ip": l.ip,
+                    "timestamp": l.timestamp,
+                    "blocked": l.blocked,
+                    "reason": l.reason,
+                })
+            })
+            .collect();
+        json_response(Value::Array(logs))
+    }
+}
+
+fn compare(a: &Value, b: &Value) -> Ordering {
+    match (a, b) {
+        (Value::Number(a), Value::Number(b)) => {
+            let (a, b) = (a.as_f64().unwrap_or(0.0), b.as_f64().unwrap_or(0.0));
+            a.partial_cmp(&b).unwrap_or(Ordering::Equal)
+        }
+        (Value::String(a), Value::String(b)) => a.cmp(b),
+        (Value::Bool(a), Value::Bool(b)) => a.cmp(b),
+        _ => Ordering::Equal,
+    }
+}
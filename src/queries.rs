@@ -1,11 +1,17 @@
 //! data types for filtering and querying information from qbittorrent
 
+use crate::data::{Hash, ProxyType};
+use crate::error::Result;
+use crate::magnet::Magnet;
+use crate::metainfo::Metainfo;
 use derive_builder;
 use derive_builder::Builder;
-use serde::{Deserialize, Serialize};
+use log::*;
+use serde::{Deserialize, Serialize, Serializer};
+use std::path::Path;
 
 /// Getting log information
-#[derive(Debug, Builder, Default, Serialize)]
+#[derive(Debug, Builder, Default, Clone, Serialize)]
 pub struct LogRequest {
     #[builder(default)]
     normal: bool,
@@ -15,8 +21,11 @@ pub struct LogRequest {
     warning: bool,
     #[builder(default)]
     critical: bool,
-    #[builder(default)]
-    last_known_id: u64,
+    /// Only entries after this id. Unset, it is not sent and the server
+    /// returns all entries, `0` would skip the first one
+    #[builder(default, setter(strip_option))]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) last_known_id: Option<u64>,
 }
 
 /// Filtering enum for use in making a `TorrentRequest`
@@ -31,14 +40,20 @@ pub enum TorrentFilter {
     Seeding,
     #[serde(rename = "completed")]
     Completed,
+    /// Same as `Stopped`, the name is picked by the server's API version
     #[serde(rename = "paused")]
     Paused,
+    #[serde(rename = "stopped")]
+    Stopped,
     #[serde(rename = "active")]
     Active,
     #[serde(rename = "inactive")]
     Inactive,
+    /// Same as `Running`, the name is picked by the server's API version
     #[serde(rename = "resumed")]
     Resumed,
+    #[serde(rename = "running")]
+    Running,
     #[serde(rename = "stalled")]
     Stalled,
     #[serde(rename = "stalled_uploading")]
@@ -54,8 +69,10 @@ pub enum TorrentFilter {
 pub struct AddTorrent {
     #[builder(default)]
     urls: Option<String>,
-    #[builder(default)]
-    torrents: Option<Vec<u8>>,
+    /// .torrent files, uploaded as `multipart/form-data`
+    #[builder(default, setter(each(name = "torrent", into)))]
+    #[serde(skip)]
+    pub(crate) torrents: Vec<TorrentFile>,
     #[builder(default)]
     savepath: Option<String>,
     #[builder(default)]
@@ -64,6 +81,7 @@ pub struct AddTorrent {
     category: Option<String>,
     #[builder(default)]
     skip_checking: Option<String>,
+    /// Add the torrents stopped, sent as `stopped` since WebUI API 2.11
     #[builder(default)]
     paused: Option<String>,
     #[builder(default)]
@@ -87,23 +105,614 @@ pub struct AddTorrent {
     first_last_piece_prio: Option<String>,
 }
 
-//#[derive(Debug, Builder, Serialize, Deserialize, Clone, Default)]
-//#[builder(setter(into, strip_option))]
-//pub struct TorrentRequest {
-//    #[builder(default)]
-//    filter: Option<TorrentFilter>,
-//    #[builder(default)]
-//    category: Option<String>,
-//    #[builder(default)]
-//    tag: Option<String>,
-//    #[builder(default)]
-//    sort: Option<String>,
-//    #[builder(default)]
-//    reverse: Option<bool>,
-//    #[builder(default)]
-//    limit: Option<u64>,
-//    #[builder(default)]
-//    offset: Option<i64>,
-//    #[builder(default)]
-//    hashes: Vec<Hash>,
-//}
+impl AddTorrent {
+    /// Hashes of the magnet links and .torrent files, other urls are skipped.
+    /// Fails if any of the magnet links is malformed. .torrent files which
+    /// fail to parse are still uploaded, but their hashes are skipped.
+    pub(crate) fn hashes(&self) -> Result<Vec<Hash>> {
+        let mut hashes = Vec::new();
+        for url in self.urls.iter().flat_map(|urls| urls.lines()) {
+            let url = url.trim();
+            if url.starts_with("magnet:") {
+                hashes.push(url.parse::<Magnet>()?.hash());
+            }
+        }
+        for file in &self.torrents {
+            match Metainfo::from_bytes(&file.data) {
+                Ok(metainfo) => hashes.push(metainfo.hash()),
+                Err(e) => warn!("{:?}: {}, skipping its hash", file.filename, e),
+            }
+        }
+        Ok(hashes)
+    }
+}
+
+/// A .torrent file to upload with `AddTorrent`
+#[derive(Debug, Clone, Default)]
+pub struct TorrentFile {
+    pub(crate) filename: String,
+    pub(crate) data: Vec<u8>,
+}
+
+impl TorrentFile {
+    pub fn new(filename: impl Into<String>, data: impl Into<Vec<u8>>) -> Self {
+        Self {
+            filename: filename.into(),
+            data: data.into(),
+        }
+    }
+
+    /// Read a .torrent file from disk.
+    pub async fn read(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let data = tokio::fs::read(path).await?;
+        let filename = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "file.torrent".into());
+        Ok(Self { filename, data })
+    }
+
+    pub fn filename(&self) -> &str {
+        &self.filename
+    }
+
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Parse the file to preview its contents before adding it.
+    pub fn metainfo(&self) -> Result<Metainfo> {
+        Metainfo::from_bytes(&self.data)
+    }
+}
+
+/// Sort key for a `TorrentRequest`, one for each field of a `Torrent`
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, Eq, PartialEq)]
+pub enum TorrentSort {
+    #[serde(rename = "added_on")]
+    AddedOn,
+    #[serde(rename = "amount_left")]
+    AmountLeft,
+    #[serde(rename = "auto_tmm")]
+    AutoTmm,
+    #[serde(rename = "category")]
+    Category,
+    #[serde(rename = "completed")]
+    Completed,
+    #[serde(rename = "completion_on")]
+    CompletionOn,
+    #[serde(rename = "dl_limit")]
+    DlLimit,
+    #[serde(rename = "dlspeed")]
+    Dlspeed,
+    #[serde(rename = "downloaded")]
+    Downloaded,
+    #[serde(rename = "downloaded_session")]
+    DownloadedSession,
+    #[serde(rename = "eta")]
+    Eta,
+    #[serde(rename = "f_l_piece_prio")]
+    FLPiecePrio,
+    #[serde(rename = "force_start")]
+    ForceStart,
+    #[serde(rename = "hash")]
+    Hash,
+    #[serde(rename = "last_activity")]
+    LastActivity,
+    #[serde(rename = "magnet_uri")]
+    MagnetUri,
+    #[serde(rename = "max_ratio")]
+    MaxRatio,
+    #[serde(rename = "max_seeding_time")]
+    MaxSeedingTime,
+    #[serde(rename = "name")]
+    Name,
+    #[serde(rename = "num_complete")]
+    NumComplete,
+    #[serde(rename = "num_incomplete")]
+    NumIncomplete,
+    #[serde(rename = "num_leechs")]
+    NumLeechs,
+    #[serde(rename = "num_seeds")]
+    NumSeeds,
+    #[serde(rename = "priority")]
+    Priority,
+    #[serde(rename = "progress")]
+    Progress,
+    #[serde(rename = "ratio")]
+    Ratio,
+    #[serde(rename = "ratio_limit")]
+    RatioLimit,
+    #[serde(rename = "save_path")]
+    SavePath,
+    #[serde(rename = "seeding_time_limit")]
+    SeedingTimeLimit,
+    #[serde(rename = "seen_complete")]
+    SeenComplete,
+    #[serde(rename = "seq_dl")]
+    SeqDl,
+    #[serde(rename = "size")]
+    Size,
+    #[serde(rename = "state")]
+    State,
+    #[serde(rename = "super_seeding")]
+    SuperSeeding,
+    #[serde(rename = "tags")]
+    Tags,
+    #[serde(rename = "time_active")]
+    TimeActive,
+    #[serde(rename = "total_size")]
+    TotalSize,
+    #[serde(rename = "tracker")]
+    Tracker,
+    #[serde(rename = "up_limit")]
+    UpLimit,
+    #[serde(rename = "uploaded")]
+    Uploaded,
+    #[serde(rename = "uploaded_session")]
+    UploadedSession,
+    #[serde(rename = "upspeed")]
+    Upspeed,
+}
+
+impl TorrentFilter {
+    /// The spelling of the filter known to the server, `stop_start` is set if
+    /// the server has the qbittorrent 5.0 names.
+    pub(crate) fn renamed(self, stop_start: bool) -> Self {
+        match (self, stop_start) {
+            (TorrentFilter::Paused, true) => TorrentFilter::Stopped,
+            (TorrentFilter::Resumed, true) => TorrentFilter::Running,
+            (TorrentFilter::Stopped, false) => TorrentFilter::Paused,
+            (TorrentFilter::Running, false) => TorrentFilter::Resumed,
+            (filter, _) => filter,
+        }
+    }
+}
+
+/// Filtered listing of torrents from `/api/v2/torrents/info`
+#[derive(Debug, Builder, Serialize, Deserialize, Clone, Default)]
+#[builder(setter(into, strip_option))]
+pub struct TorrentRequest {
+    #[builder(default)]
+    pub(crate) filter: Option<TorrentFilter>,
+    #[builder(default)]
+    category: Option<String>,
+    #[builder(default)]
+    tag: Option<String>,
+    #[builder(default)]
+    sort: Option<TorrentSort>,
+    #[builder(default)]
+    reverse: Option<bool>,
+    #[builder(default)]
+    limit: Option<u64>,
+    #[builder(default)]
+    offset: Option<i64>,
+    #[builder(default)]
+    #[serde(
+        serialize_with = "serialize_hashes",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub(crate) hashes: Vec<Hash>,
+    #[builder(default)]
+    private: Option<bool>,
+}
+
+fn serialize_hashes<S: Serializer>(
+    hashes: &[Hash],
+    serializer: S,
+) -> std::result::Result<S::Ok, S::Error> {
+    let refs: Vec<&str> = hashes.iter().map(|h| h.as_str()).collect();
+    serializer.serialize_str(&refs.join("|"))
+}
+
+/// Changes to the application preferences, only the fields which are set are
+/// sent to `/api/v2/app/setPreferences`
+#[derive(Debug, Builder, Serialize, Clone, Default)]
+#[builder(setter(into, strip_option), default)]
+pub struct PreferencesPatch {
+    // Downloads
+    /// Default save path for torrents
+    #[serde(skip_serializing_if = "Option::is_none")]
+    save_path: Option<String>,
+    /// Keep incomplete torrents in `temp_path`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    temp_path_enabled: Option<bool>,
+    /// Path for incomplete torrents
+    #[serde(skip_serializing_if = "Option::is_none")]
+    temp_path: Option<String>,
+    /// Copy .torrent files to this directory
+    #[serde(skip_serializing_if = "Option::is_none")]
+    export_dir: Option<String>,
+    /// Copy .torrent files of finished downloads to this directory
+    #[serde(skip_serializing_if = "Option::is_none")]
+    export_dir_fin: Option<String>,
+    /// Pre-allocate disk space for all files
+    #[serde(skip_serializing_if = "Option::is_none")]
+    preallocate_all: Option<bool>,
+    /// Append `.!qB` to incomplete files
+    #[serde(skip_serializing_if = "Option::is_none")]
+    incomplete_files_ext: Option<bool>,
+    /// Delete .torrent files afterwards (0 never, 1 if added, 2 always)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    auto_delete_mode: Option<i64>,
+    /// Automatically add `add_trackers` to new downloads
+    #[serde(skip_serializing_if = "Option::is_none")]
+    add_trackers_enabled: Option<bool>,
+    /// Newline separated trackers added to new downloads
+    #[serde(skip_serializing_if = "Option::is_none")]
+    add_trackers: Option<String>,
+    /// Run an external program on torrent completion
+    #[serde(skip_serializing_if = "Option::is_none")]
+    autorun_enabled: Option<bool>,
+    /// Program run on torrent completion
+    #[serde(skip_serializing_if = "Option::is_none")]
+    autorun_program: Option<String>,
+    /// Automatic torrent management is the default for new torrents
+    #[serde(skip_serializing_if = "Option::is_none")]
+    auto_tmm_enabled: Option<bool>,
+    /// Relocate torrent when its category changes
+    #[serde(skip_serializing_if = "Option::is_none")]
+    torrent_changed_tmm_enabled: Option<bool>,
+    /// Relocate affected torrents when the default save path changes
+    #[serde(skip_serializing_if = "Option::is_none")]
+    save_path_changed_tmm_enabled: Option<bool>,
+    /// Relocate affected torrents when their category save path changes
+    #[serde(skip_serializing_if = "Option::is_none")]
+    category_changed_tmm_enabled: Option<bool>,
+
+    // Connection
+    /// Port for incoming connections
+    #[serde(skip_serializing_if = "Option::is_none")]
+    listen_port: Option<i64>,
+    /// Use a random port on every startup
+    #[serde(skip_serializing_if = "Option::is_none")]
+    random_port: Option<bool>,
+    /// Use UPnP/NAT-PMP to forward `listen_port`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    upnp: Option<bool>,
+    /// Allowed protocols (0 TCP and uTP, 1 TCP, 2 uTP)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    bittorrent_protocol: Option<i64>,
+    /// Maximum global number of connections, -1 for unlimited
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_connec: Option<i64>,
+    /// Maximum number of connections per torrent, -1 for unlimited
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_connec_per_torrent: Option<i64>,
+    /// Maximum global number of upload slots, -1 for unlimited
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_uploads: Option<i64>,
+    /// Maximum number of upload slots per torrent, -1 for unlimited
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_uploads_per_torrent: Option<i64>,
+    /// Lower bound of the outgoing port range, 0 to disable
+    #[serde(skip_serializing_if = "Option::is_none")]
+    outgoing_ports_min: Option<i64>,
+    /// Upper bound of the outgoing port range, 0 to disable
+    #[serde(skip_serializing_if = "Option::is_none")]
+    outgoing_ports_max: Option<i64>,
+    /// Network interface used for connections
+    #[serde(skip_serializing_if = "Option::is_none")]
+    current_network_interface: Option<String>,
+    /// Interface address used for connections
+    #[serde(skip_serializing_if = "Option::is_none")]
+    current_interface_address: Option<String>,
+
+    // Speed
+    /// Global download limit (bytes/s), -1 for unlimited
+    #[serde(skip_serializing_if = "Option::is_none")]
+    dl_limit: Option<i64>,
+    /// Global upload limit (bytes/s), -1 for unlimited
+    #[serde(skip_serializing_if = "Option::is_none")]
+    up_limit: Option<i64>,
+    /// Alternative download limit (bytes/s)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    alt_dl_limit: Option<i64>,
+    /// Alternative upload limit (bytes/s)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    alt_up_limit: Option<i64>,
+    /// Apply rate limits to the uTP protocol
+    #[serde(skip_serializing_if = "Option::is_none")]
+    limit_utp_rate: Option<bool>,
+    /// Apply rate limits to transport overhead
+    #[serde(skip_serializing_if = "Option::is_none")]
+    limit_tcp_overhead: Option<bool>,
+    /// Apply rate limits to peers on the LAN
+    #[serde(skip_serializing_if = "Option::is_none")]
+    limit_lan_peers: Option<bool>,
+    /// Switch to the alternative limits on a schedule
+    #[serde(skip_serializing_if = "Option::is_none")]
+    scheduler_enabled: Option<bool>,
+    /// Scheduler start hour
+    #[serde(skip_serializing_if = "Option::is_none")]
+    schedule_from_hour: Option<i64>,
+    /// Scheduler start minute
+    #[serde(skip_serializing_if = "Option::is_none")]
+    schedule_from_min: Option<i64>,
+    /// Scheduler end hour
+    #[serde(skip_serializing_if = "Option::is_none")]
+    schedule_to_hour: Option<i64>,
+    /// Scheduler end minute
+    #[serde(skip_serializing_if = "Option::is_none")]
+    schedule_to_min: Option<i64>,
+    /// Scheduler days (0 every day, 1 weekdays, 2 weekends, 3-9 Monday-Sunday)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    scheduler_days: Option<i64>,
+
+    // BitTorrent
+    /// DHT is enabled
+    #[serde(skip_serializing_if = "Option::is_none")]
+    dht: Option<bool>,
+    /// PeX is enabled
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pex: Option<bool>,
+    /// Local peer discovery is enabled
+    #[serde(skip_serializing_if = "Option::is_none")]
+    lsd: Option<bool>,
+    /// Encryption mode (0 prefer, 1 force on, 2 force off)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    encryption: Option<i64>,
+    /// Anonymous mode is enabled
+    #[serde(skip_serializing_if = "Option::is_none")]
+    anonymous_mode: Option<bool>,
+    /// Torrent queueing is enabled
+    #[serde(skip_serializing_if = "Option::is_none")]
+    queueing_enabled: Option<bool>,
+    /// Maximum number of active downloads
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_active_downloads: Option<i64>,
+    /// Maximum number of active uploads
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_active_uploads: Option<i64>,
+    /// Maximum number of active torrents
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_active_torrents: Option<i64>,
+    /// Exclude slow torrents from the active limits
+    #[serde(skip_serializing_if = "Option::is_none")]
+    dont_count_slow_torrents: Option<bool>,
+    /// Download rate below which a torrent is slow (KiB/s)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    slow_torrent_dl_rate_threshold: Option<i64>,
+    /// Upload rate below which a torrent is slow (KiB/s)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    slow_torrent_ul_rate_threshold: Option<i64>,
+    /// Seconds a torrent must be slow before it is inactive
+    #[serde(skip_serializing_if = "Option::is_none")]
+    slow_torrent_inactive_timer: Option<i64>,
+    /// Apply the global share ratio limit
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_ratio_enabled: Option<bool>,
+    /// Global share ratio limit
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_ratio: Option<f64>,
+    /// Apply the global seeding time limit
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_seeding_time_enabled: Option<bool>,
+    /// Global seeding time limit (minutes)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_seeding_time: Option<i64>,
+    /// Action when a limit is reached (0 pause, 1 remove, 2 remove with files, 3 super seeding)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_ratio_act: Option<i64>,
+
+    // Proxy
+    /// Proxy type
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) proxy_type: Option<ProxyType>,
+    /// Proxy host
+    #[serde(skip_serializing_if = "Option::is_none")]
+    proxy_ip: Option<String>,
+    /// Proxy port
+    #[serde(skip_serializing_if = "Option::is_none")]
+    proxy_port: Option<i64>,
+    /// Proxy requires authentication
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) proxy_auth_enabled: Option<bool>,
+    /// Proxy username
+    #[serde(skip_serializing_if = "Option::is_none")]
+    proxy_username: Option<String>,
+    /// Proxy password
+    #[serde(skip_serializing_if = "Option::is_none")]
+    proxy_password: Option<String>,
+    /// Use the proxy for peer connections
+    #[serde(skip_serializing_if = "Option::is_none")]
+    proxy_peer_connections: Option<bool>,
+    /// Use the proxy for torrents only
+    #[serde(skip_serializing_if = "Option::is_none")]
+    proxy_torrents_only: Option<bool>,
+
+    // IP filtering
+    /// IP filtering is enabled
+    #[serde(skip_serializing_if = "Option::is_none")]
+    ip_filter_enabled: Option<bool>,
+    /// Path to the IP filter file
+    #[serde(skip_serializing_if = "Option::is_none")]
+    ip_filter_path: Option<String>,
+    /// Apply the IP filter to trackers
+    #[serde(skip_serializing_if = "Option::is_none")]
+    ip_filter_trackers: Option<bool>,
+    /// Newline separated banned IP addresses
+    #[serde(rename = "banned_IPs")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    banned_ips: Option<String>,
+
+    // WebUI
+    /// Current locale
+    #[serde(skip_serializing_if = "Option::is_none")]
+    locale: Option<String>,
+    /// Semicolon separated allowed domains
+    #[serde(skip_serializing_if = "Option::is_none")]
+    web_ui_domain_list: Option<String>,
+    /// Address the WebUI listens on
+    #[serde(skip_serializing_if = "Option::is_none")]
+    web_ui_address: Option<String>,
+    /// Port the WebUI listens on
+    #[serde(skip_serializing_if = "Option::is_none")]
+    web_ui_port: Option<i64>,
+    /// Use UPnP/NAT-PMP to forward `web_ui_port`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    web_ui_upnp: Option<bool>,
+    /// WebUI username
+    #[serde(skip_serializing_if = "Option::is_none")]
+    web_ui_username: Option<String>,
+    /// WebUI password, write only
+    #[serde(skip_serializing_if = "Option::is_none")]
+    web_ui_password: Option<String>,
+    /// Serve the WebUI over HTTPS
+    #[serde(skip_serializing_if = "Option::is_none")]
+    use_https: Option<bool>,
+    /// Skip authentication for clients on localhost
+    #[serde(skip_serializing_if = "Option::is_none")]
+    bypass_local_auth: Option<bool>,
+    /// Skip authentication for whitelisted subnets
+    #[serde(skip_serializing_if = "Option::is_none")]
+    bypass_auth_subnet_whitelist_enabled: Option<bool>,
+    /// Newline separated whitelisted subnets
+    #[serde(skip_serializing_if = "Option::is_none")]
+    bypass_auth_subnet_whitelist: Option<String>,
+    /// Seconds before an idle session expires
+    #[serde(skip_serializing_if = "Option::is_none")]
+    web_ui_session_timeout: Option<i64>,
+    /// Failed logins before a client is banned
+    #[serde(skip_serializing_if = "Option::is_none")]
+    web_ui_max_auth_fail_count: Option<i64>,
+    /// Seconds a client is banned for
+    #[serde(skip_serializing_if = "Option::is_none")]
+    web_ui_ban_duration: Option<i64>,
+    /// Clickjacking protection is enabled
+    #[serde(skip_serializing_if = "Option::is_none")]
+    web_ui_clickjacking_protection_enabled: Option<bool>,
+    /// CSRF protection is enabled
+    #[serde(skip_serializing_if = "Option::is_none")]
+    web_ui_csrf_protection_enabled: Option<bool>,
+    /// Serve an alternative WebUI
+    #[serde(skip_serializing_if = "Option::is_none")]
+    alternative_webui_enabled: Option<bool>,
+    /// Path to the alternative WebUI
+    #[serde(skip_serializing_if = "Option::is_none")]
+    alternative_webui_path: Option<String>,
+    /// Update a dynamic DNS domain
+    #[serde(skip_serializing_if = "Option::is_none")]
+    dyndns_enabled: Option<bool>,
+    /// Dynamic DNS service (0 DynDNS, 1 NOIP)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    dyndns_service: Option<i64>,
+    /// Dynamic DNS username
+    #[serde(skip_serializing_if = "Option::is_none")]
+    dyndns_username: Option<String>,
+    /// Dynamic DNS password
+    #[serde(skip_serializing_if = "Option::is_none")]
+    dyndns_password: Option<String>,
+    /// Dynamic DNS domain
+    #[serde(skip_serializing_if = "Option::is_none")]
+    dyndns_domain: Option<String>,
+
+    // RSS
+    /// Feed refresh interval (minutes)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rss_refresh_interval: Option<i64>,
+    /// Maximum number of stored articles per feed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rss_max_articles_per_feed: Option<i64>,
+    /// Feeds are fetched
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rss_processing_enabled: Option<bool>,
+    /// Auto download rules are applied
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rss_auto_downloading_enabled: Option<bool>,
+    /// Download REPACK/PROPER episodes
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rss_download_repack_proper_episodes: Option<bool>,
+    /// Newline separated smart episode filters
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rss_smart_episode_filters: Option<String>,
+
+    // Email notifications
+    /// Send an email on torrent completion
+    #[serde(skip_serializing_if = "Option::is_none")]
+    mail_notification_enabled: Option<bool>,
+    /// Sender address
+    #[serde(skip_serializing_if = "Option::is_none")]
+    mail_notification_sender: Option<String>,
+    /// Recipient address
+    #[serde(skip_serializing_if = "Option::is_none")]
+    mail_notification_email: Option<String>,
+    /// SMTP server
+    #[serde(skip_serializing_if = "Option::is_none")]
+    mail_notification_smtp: Option<String>,
+    /// Use SSL for the SMTP connection
+    #[serde(skip_serializing_if = "Option::is_none")]
+    mail_notification_ssl_enabled: Option<bool>,
+    /// SMTP server requires authentication
+    #[serde(skip_serializing_if = "Option::is_none")]
+    mail_notification_auth_enabled: Option<bool>,
+    /// SMTP username
+    #[serde(skip_serializing_if = "Option::is_none")]
+    mail_notification_username: Option<String>,
+    /// SMTP password
+    #[serde(skip_serializing_if = "Option::is_none")]
+    mail_notification_password: Option<String>,
+
+    // Advanced
+    /// Disk cache size (MiB), -1 for automatic
+    #[serde(skip_serializing_if = "Option::is_none")]
+    disk_cache: Option<i64>,
+    /// Disk cache expiry interval (seconds)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    disk_cache_ttl: Option<i64>,
+    /// Use the OS cache
+    #[serde(skip_serializing_if = "Option::is_none")]
+    enable_os_cache: Option<bool>,
+    /// Number of asynchronous I/O threads
+    #[serde(skip_serializing_if = "Option::is_none")]
+    async_io_threads: Option<i64>,
+    /// Maximum number of open files
+    #[serde(skip_serializing_if = "Option::is_none")]
+    file_pool_size: Option<i64>,
+    /// Memory used for checking torrents (MiB)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    checking_memory_use: Option<i64>,
+    /// Send buffer watermark (KiB)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    send_buffer_watermark: Option<i64>,
+    /// Send buffer low watermark (KiB)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    send_buffer_low_watermark: Option<i64>,
+    /// Send buffer watermark factor (percent)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    send_buffer_watermark_factor: Option<i64>,
+    /// Resume data save interval (minutes)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    save_resume_data_interval: Option<i64>,
+    /// Recheck torrents on completion
+    #[serde(skip_serializing_if = "Option::is_none")]
+    recheck_completed_torrents: Option<bool>,
+    /// Resolve peer countries
+    #[serde(skip_serializing_if = "Option::is_none")]
+    resolve_peer_countries: Option<bool>,
+    /// Announce to all trackers in a tier
+    #[serde(skip_serializing_if = "Option::is_none")]
+    announce_to_all_trackers: Option<bool>,
+    /// Announce to all tiers
+    #[serde(skip_serializing_if = "Option::is_none")]
+    announce_to_all_tiers: Option<bool>,
+    /// IP address reported to trackers
+    #[serde(skip_serializing_if = "Option::is_none")]
+    announce_ip: Option<String>,
+    /// Run the embedded tracker
+    #[serde(skip_serializing_if = "Option::is_none")]
+    enable_embedded_tracker: Option<bool>,
+    /// Port of the embedded tracker
+    #[serde(skip_serializing_if = "Option::is_none")]
+    embedded_tracker_port: Option<i64>,
+    /// Allow multiple connections from the same IP
+    #[serde(skip_serializing_if = "Option::is_none")]
+    enable_multi_connections_from_same_ip: Option<bool>,
+    /// Upload slots behavior (0 fixed, 1 upload rate based)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    upload_slots_behavior: Option<i64>,
+    /// Upload choking algorithm (0 round-robin, 1 fastest upload, 2 anti-leech)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    upload_choking_algorithm: Option<i64>,
+}
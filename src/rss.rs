@@ -0,0 +1,214 @@
+//! RSS feeds, folders and auto-download rules from `/api/v2/rss/*`
+
+use crate::api::Api;
+use crate::error::Result;
+use derive_builder::Builder;
+use derive_getters::Getters;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+/// Separator between the components of an RSS item path.
+pub const PATH_SEPARATOR: char = '\\';
+
+/// A node in the RSS tree, the root returned by `Api::rss_items` is a folder.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum RssItem {
+    Feed(RssFeed),
+    Folder(BTreeMap<String, RssItem>),
+}
+
+impl RssItem {
+    /// All feeds below this item, paired with their full item path.
+    pub fn feeds(&self) -> Vec<(String, &RssFeed)> {
+        let mut ret = Vec::new();
+        self.collect_feeds(String::new(), &mut ret);
+        ret
+    }
+
+    fn collect_feeds<'a>(&'a self, path: String, ret: &mut Vec<(String, &'a RssFeed)>) {
+        match self {
+            RssItem::Feed(feed) => ret.push((path, feed)),
+            RssItem::Folder(items) => {
+                for (name, item) in items {
+                    let path = if path.is_empty() {
+                        name.clone()
+                    } else {
+                        format!("{}{}{}", path, PATH_SEPARATOR, name)
+                    };
+                    item.collect_feeds(path, ret);
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, Getters)]
+#[serde(rename_all = "camelCase")]
+pub struct RssFeed {
+    uid: String,
+    url: String,
+    // Only present when requested with data.
+    title: Option<String>,
+    last_build_date: Option<String>,
+    is_loading: Option<bool>,
+    has_error: Option<bool>,
+    #[serde(default)]
+    articles: Vec<RssArticle>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, Getters)]
+pub struct RssArticle {
+    id: String,
+    title: String,
+    date: String,
+    description: Option<String>,
+    link: Option<String>,
+    author: Option<String>,
+    category: Option<String>,
+    #[serde(rename = "torrentURL")]
+    torrent_url: Option<String>,
+    #[serde(rename = "isRead", default)]
+    is_read: bool,
+}
+
+/// RSS auto-download rule, see `Api::rss_set_rule`
+#[derive(Debug, Clone, Deserialize, Serialize, Builder, Getters)]
+#[serde(rename_all = "camelCase", default)]
+#[builder(setter(into), default)]
+pub struct AutoDownloadRule {
+    enabled: bool,
+    must_contain: String,
+    must_not_contain: String,
+    use_regex: bool,
+    /// Episode filter definition, e.g. `1x01-;`
+    episode_filter: String,
+    smart_filter: bool,
+    previously_matched_episodes: Vec<String>,
+    /// Urls of the feeds this rule applies to
+    affected_feeds: Vec<String>,
+    /// Ignore subsequent matches for this many days
+    ignore_days: i64,
+    last_match: String,
+    /// `None` uses the global setting
+    #[builder(setter(strip_option))]
+    add_paused: Option<bool>,
+    assigned_category: String,
+    save_path: String,
+}
+
+impl Default for AutoDownloadRule {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            must_contain: String::new(),
+            must_not_contain: String::new(),
+            use_regex: false,
+            episode_filter: String::new(),
+            smart_filter: false,
+            previously_matched_episodes: Vec::new(),
+            affected_feeds: Vec::new(),
+            ignore_days: 0,
+            last_match: String::new(),
+            add_paused: None,
+            assigned_category: String::new(),
+            save_path: String::new(),
+        }
+    }
+}
+
+impl Api {
+    //
+    // RSS items
+    //
+
+    /// Create a folder, `path` is the full item path e.g. `Folder\Subfolder`.
+    pub async fn rss_add_folder(&self, path: &str) -> Result<()> {
+        let mut form = HashMap::new();
+        form.insert("path", path);
+        self.post_status("/api/v2/rss/addFolder", &form).await
+    }
+
+    /// Subscribe to the feed at `url`, `path` is the full item path.
+    pub async fn rss_add_feed(&self, url: &str, path: &str) -> Result<()> {
+        let mut form = HashMap::new();
+        form.insert("url", url);
+        form.insert("path", path);
+        self.post_status("/api/v2/rss/addFeed", &form).await
+    }
+
+    pub async fn rss_remove_item(&self, path: &str) -> Result<()> {
+        let mut form = HashMap::new();
+        form.insert("path", path);
+        self.post_status("/api/v2/rss/removeItem", &form).await
+    }
+
+    pub async fn rss_move_item(&self, item_path: &str, dest_path: &str) -> Result<()> {
+        let mut form = HashMap::new();
+        form.insert("itemPath", item_path);
+        form.insert("destPath", dest_path);
+        self.post_status("/api/v2/rss/moveItem", &form).await
+    }
+
+    /// The tree of all folders and feeds, including the feed articles when
+    /// `with_data` is set.
+    pub async fn rss_items(&self, with_data: bool) -> Result<RssItem> {
+        let mut form = HashMap::new();
+        form.insert("withData", with_data);
+        self.post_decode("/api/v2/rss/items", &form).await
+    }
+
+    /// Mark a single article, or every article of the item if `article_id` is
+    /// `None`, as read.
+    pub async fn rss_mark_as_read(&self, item_path: &str, article_id: Option<&str>) -> Result<()> {
+        let mut form = HashMap::new();
+        form.insert("itemPath", item_path);
+        if let Some(article_id) = article_id {
+            form.insert("articleId", article_id);
+        }
+        self.post_status("/api/v2/rss/markAsRead", &form).await
+    }
+
+    pub async fn rss_refresh_item(&self, item_path: &str) -> Result<()> {
+        let mut form = HashMap::new();
+        form.insert("itemPath", item_path);
+        self.post_status("/api/v2/rss/refreshItem", &form).await
+    }
+
+    //
+    // Auto-download rules
+    //
+
+    /// Create the rule `name`, or replace it if it already exists.
+    pub async fn rss_set_rule(&self, name: &str, rule: &AutoDownloadRule) -> Result<()> {
+        let mut form = HashMap::new();
+        form.insert("ruleName", name.to_string());
+        form.insert("ruleDef", serde_json::to_string(rule)?);
+        self.post_status("/api/v2/rss/setRule", &form).await
+    }
+
+    pub async fn rss_rename_rule(&self, name: &str, new_name: &str) -> Result<()> {
+        let mut form = HashMap::new();
+        form.insert("ruleName", name);
+        form.insert("newRuleName", new_name);
+        self.post_status("/api/v2/rss/renameRule", &form).await
+    }
+
+    pub async fn rss_remove_rule(&self, name: &str) -> Result<()> {
+        let mut form = HashMap::new();
+        form.insert("ruleName", name);
+        self.post_status("/api/v2/rss/removeRule", &form).await
+    }
+
+    pub async fn rss_rules(&self) -> Result<HashMap<String, AutoDownloadRule>> {
+        self.post_decode("/api/v2/rss/rules", &()).await
+    }
+
+    /// Titles of the articles matched by the rule, keyed by feed name.
+    pub async fn rss_matching_articles(&self, name: &str) -> Result<HashMap<String, Vec<String>>> {
+        let mut form = HashMap::new();
+        form.insert("ruleName", name);
+        self.post_decode("/api/v2/rss/matchingArticles", &form)
+            .await
+    }
+}
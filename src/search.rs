@@ -0,0 +1,215 @@
+//! Search jobs and search plugins from `/api/v2/search/*`
+
+use crate::api::Api;
+use crate::error::{Error, Result};
+use derive_getters::Getters;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::Duration;
+
+#[derive(Debug, Deserialize, Serialize, Copy, Clone, Eq, PartialEq)]
+pub enum SearchStatus {
+    Running,
+    Stopped,
+}
+
+#[derive(Debug, Deserialize, Clone, Getters)]
+pub struct SearchJobStatus {
+    id: u64,
+    status: SearchStatus,
+    /// Number of results found so far
+    total: u64,
+}
+
+#[derive(Debug, Deserialize, Clone, Getters)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchResult {
+    /// Url of the torrent's description page
+    descr_link: String,
+    file_name: String,
+    /// Size in bytes, -1 if unknown
+    file_size: i64,
+    /// Torrent download link, usually a magnet uri
+    file_url: String,
+    nb_leechers: i64,
+    nb_seeders: i64,
+    site_url: String,
+    /// Publication date in seconds since epoch, not reported by older versions
+    pub_date: Option<i64>,
+}
+
+#[derive(Debug, Deserialize, Getters)]
+pub struct SearchResults {
+    results: Vec<SearchResult>,
+    status: SearchStatus,
+    total: u64,
+}
+
+#[derive(Debug, Deserialize, Clone, Getters)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchPlugin {
+    enabled: bool,
+    full_name: String,
+    name: String,
+    supported_categories: Vec<SearchCategory>,
+    url: String,
+    version: String,
+}
+
+/// Category supported by a plugin, older versions only report the name.
+#[derive(Debug, Deserialize, Clone)]
+#[serde(untagged)]
+pub enum SearchCategory {
+    Category { id: String, name: String },
+    Name(String),
+}
+
+/// Handle to a running search job.
+///
+/// Results are fetched incrementally, each call to `next_results` only returns
+/// the results found since the previous call.
+#[derive(Debug)]
+pub struct SearchJob<'a> {
+    api: &'a Api,
+    id: u64,
+    offset: u64,
+}
+
+impl SearchJob<'_> {
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// Wait for new results, polling every `interval`. Returns `None` once the
+    /// job has stopped and all of its results have been returned.
+    pub async fn next_results(&mut self, interval: Duration) -> Result<Option<Vec<SearchResult>>> {
+        loop {
+            let results = self.api.search_results(self.id, None, self.offset).await?;
+            if !results.results.is_empty() {
+                self.offset += results.results.len() as u64;
+                return Ok(Some(results.results));
+            }
+            if results.status == SearchStatus::Stopped {
+                return Ok(None);
+            }
+            tokio::time::sleep(interval).await;
+        }
+    }
+
+    pub async fn status(&self) -> Result<SearchJobStatus> {
+        let mut status = self.api.search_status(Some(self.id)).await?;
+        status.pop().ok_or(Error::BadResponse)
+    }
+
+    pub async fn stop(&self) -> Result<()> {
+        self.api.search_stop(self.id).await
+    }
+
+    /// Stop the job and discard its results.
+    pub async fn delete(self) -> Result<()> {
+        self.api.search_delete(self.id).await
+    }
+}
+
+#[derive(Deserialize)]
+struct SearchStart {
+    id: u64,
+}
+
+impl Api {
+    //
+    // Search jobs
+    //
+
+    /// Start searching for `pattern`. `plugins` is `all`, `enabled` or a `|`
+    /// separated list of plugin names and `category` is `all` or a category
+    /// supported by the plugins.
+    pub async fn search_start(
+        &self,
+        pattern: &str,
+        plugins: &str,
+        category: &str,
+    ) -> Result<SearchJob<'_>> {
+        let mut form = HashMap::new();
+        form.insert("pattern", pattern);
+        form.insert("plugins", plugins);
+        form.insert("category", category);
+        let start: SearchStart = self.post_decode("/api/v2/search/start", &form).await?;
+        Ok(SearchJob {
+            api: self,
+            id: start.id,
+            offset: 0,
+        })
+    }
+
+    pub async fn search_stop(&self, id: u64) -> Result<()> {
+        let mut form = HashMap::new();
+        form.insert("id", id);
+        self.post_status("/api/v2/search/stop", &form).await
+    }
+
+    /// Status of the job `id`, or of all jobs if `None`.
+    pub async fn search_status(&self, id: Option<u64>) -> Result<Vec<SearchJobStatus>> {
+        let mut form = HashMap::new();
+        if let Some(id) = id {
+            form.insert("id", id);
+        }
+        self.post_decode("/api/v2/search/status", &form).await
+    }
+
+    /// Up to `limit` results of the job `id`, starting at `offset`.
+    pub async fn search_results(
+        &self,
+        id: u64,
+        limit: Option<u64>,
+        offset: u64,
+    ) -> Result<SearchResults> {
+        let mut form = HashMap::new();
+        form.insert("id", id);
+        form.insert("offset", offset);
+        if let Some(limit) = limit {
+            form.insert("limit", limit);
+        }
+        self.post_decode("/api/v2/search/results", &form).await
+    }
+
+    pub async fn search_delete(&self, id: u64) -> Result<()> {
+        let mut form = HashMap::new();
+        form.insert("id", id);
+        self.post_status("/api/v2/search/delete", &form).await
+    }
+
+    //
+    // Search plugins
+    //
+
+    pub async fn search_plugins(&self) -> Result<Vec<SearchPlugin>> {
+        self.post_decode("/api/v2/search/plugins", &()).await
+    }
+
+    /// Install plugins from urls or local file paths.
+    pub async fn search_install_plugins(&self, sources: &[String]) -> Result<()> {
+        let mut form = HashMap::new();
+        form.insert("sources", sources.join("|"));
+        self.post_status("/api/v2/search/installPlugin", &form)
+            .await
+    }
+
+    pub async fn search_uninstall_plugins(&self, names: &[String]) -> Result<()> {
+        let mut form = HashMap::new();
+        form.insert("names", names.join("|"));
+        self.post_status("/api/v2/search/uninstallPlugin", &form)
+            .await
+    }
+
+    pub async fn search_enable_plugins(&self, names: &[String], enable: bool) -> Result<()> {
+        let mut form = HashMap::new();
+        form.insert("names", names.join("|"));
+        form.insert("enable", enable.to_string());
+        self.post_status("/api/v2/search/enablePlugin", &form).await
+    }
+
+    pub async fn search_update_plugins(&self) -> Result<()> {
+        self.post_status("/api/v2/search/updatePlugins", &()).await
+    }
+}
@@ -0,0 +1,345 @@
+//! Incremental state tracking on top of the `/api/v2/sync/*` endpoints.
+
+use crate::api::Api;
+use crate::data::{Category, Hash, MainData, Peer, ServerState, Torrent, TorrentPeers};
+use crate::error::Result;
+use crate::traits::TorrentApi;
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value};
+use std::collections::{HashMap, HashSet};
+
+/// A keyed collection of objects which are updated field-by-field.
+///
+/// The raw json of every object is kept so that partial updates can be merged
+/// before deserializing into the typed representation.
+#[derive(Debug)]
+pub(crate) struct SyncMap<T> {
+    key_field: Option<&'static str>,
+    raw: HashMap<String, Map<String, Value>>,
+    items: HashMap<String, T>,
+}
+
+impl<T: DeserializeOwned> SyncMap<T> {
+    /// `key_field` is filled in with the key if the object does not contain
+    /// it already.
+    pub(crate) fn new(key_field: Option<&'static str>) -> Self {
+        Self {
+            key_field,
+            raw: HashMap::new(),
+            items: HashMap::new(),
+        }
+    }
+
+    /// Merge and deserialize the changes without modifying the map, so that
+    /// a failure leaves it untouched. A full update replaces every object.
+    pub(crate) fn stage(
+        &self,
+        full_update: bool,
+        patches: impl IntoIterator<Item = (String, Map<String, Value>)>,
+        removed: Vec<String>,
+    ) -> Result<Staged<T>> {
+        let mut updated = Vec::new();
+        for (key, patch) in patches {
+            let mut raw = match self.raw.get(&key) {
+                Some(raw) if !full_update => raw.clone(),
+                _ => Map::new(),
+            };
+            raw.extend(patch);
+            if let Some(key_field) = self.key_field {
+                raw.entry(key_field)
+                    .or_insert_with(|| Value::String(key.clone()));
+            }
+            let item = serde_json::from_value(Value::Object(raw.clone()))?;
+            updated.push((key, raw, item));
+        }
+        Ok(Staged {
+            full_update,
+            updated,
+            removed,
+        })
+    }
+
+    pub(crate) fn commit(&mut self, staged: Staged<T>) {
+        if staged.full_update {
+            self.raw.clear();
+            self.items.clear();
+        }
+        for (key, raw, item) in staged.updated {
+            self.raw.insert(key.clone(), raw);
+            self.items.insert(key, item);
+        }
+        for key in &staged.removed {
+            self.remove(key);
+        }
+    }
+
+    pub(crate) fn remove(&mut self, key: &str) -> Option<T> {
+        self.raw.remove(key);
+        self.items.remove(key)
+    }
+
+    pub(crate) fn get(&self, key: &str) -> Option<&T> {
+        self.items.get(key)
+    }
+
+    pub(crate) fn items(&self) -> &HashMap<String, T> {
+        &self.items
+    }
+}
+
+/// Changes to a `SyncMap` from `SyncMap::stage`, ready to be committed.
+pub(crate) struct Staged<T> {
+    full_update: bool,
+    updated: Vec<(String, Map<String, Value>, T)>,
+    removed: Vec<String>,
+}
+
+/// Local copy of the qbittorrent main data, kept up to date by polling
+/// `/api/v2/sync/maindata` with the last seen response id.
+#[derive(Debug)]
+pub struct SyncState {
+    rid: u64,
+    torrents: SyncMap<Torrent>,
+    categories: SyncMap<Category>,
+    tags: HashSet<String>,
+    server_state_raw: Map<String, Value>,
+    server_state: ServerState,
+}
+
+impl Default for SyncState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SyncState {
+    pub fn new() -> Self {
+        Self {
+            rid: 0,
+            torrents: SyncMap::new(Some("hash")),
+            categories: SyncMap::new(Some("name")),
+            tags: HashSet::new(),
+            server_state_raw: Map::new(),
+            server_state: ServerState::default(),
+        }
+    }
+
+    /// Request the changes since the last update and apply them.
+    pub async fn update(&mut self, api: &Api) -> Result<()> {
+        let data = api.sync_maindata(self.rid).await?;
+        self.apply(data)
+    }
+
+    /// Apply a (possibly partial) maindata response to the local state.
+    ///
+    /// If the response fails to deserialize the state is unchanged, but the
+    /// rid is reset so that the next update requests a full update rather
+    /// than the same diff again.
+    pub fn apply(&mut self, data: MainData) -> Result<()> {
+        let MainData {
+            rid,
+            full_update,
+            torrents,
+            torrents_removed,
+            categories,
+            categories_removed,
+            tags,
+            tags_removed,
+            server_state,
+        } = data;
+
+        // Everything which can fail is done before the state is modified.
+        let staged = (|| {
+            let torrents = self
+                .torrents
+                .stage(full_update, torrents, torrents_removed)?;
+            let categories = self
+                .categories
+                .stage(full_update, categories, categories_removed)?;
+            let server_state = if full_update || !server_state.is_empty() {
+                let mut raw = match full_update {
+                    true => Map::new(),
+                    false => self.server_state_raw.clone(),
+                };
+                raw.extend(server_state);
+                let parsed = serde_json::from_value(Value::Object(raw.clone()))?;
+                Some((raw, parsed))
+            } else {
+                None
+            };
+            Ok((torrents, categories, server_state))
+        })();
+        let (torrents, categories, server_state) = match staged {
+            Ok(staged) => staged,
+            Err(e) => {
+                self.rid = 0;
+                return Err(e);
+            }
+        };
+
+        self.torrents.commit(torrents);
+        self.categories.commit(categories);
+        if full_update {
+            self.tags.clear();
+        }
+        self.tags.extend(tags);
+        for tag in &tags_removed {
+            self.tags.remove(tag);
+        }
+        if let Some((raw, parsed)) = server_state {
+            self.server_state_raw = raw;
+            self.server_state = parsed;
+        }
+
+        self.rid = rid;
+        Ok(())
+    }
+
+    /// Response id of the last applied update.
+    pub fn rid(&self) -> u64 {
+        self.rid
+    }
+
+    pub fn torrents(&self) -> impl Iterator<Item = &Torrent> {
+        self.torrents.items().values()
+    }
+
+    pub fn torrent(&self, hash: &str) -> Option<&Torrent> {
+        self.torrents.get(hash)
+    }
+
+    pub fn categories(&self) -> &HashMap<String, Category> {
+        self.categories.items()
+    }
+
+    pub fn tags(&self) -> &HashSet<String> {
+        &self.tags
+    }
+
+    pub fn server_state(&self) -> &ServerState {
+        &self.server_state
+    }
+}
+
+/// Local copy of the peers of a single torrent, kept up to date by polling
+/// `/api/v2/sync/torrentPeers` with the last seen response id.
+#[derive(Debug)]
+pub struct PeerSyncState {
+    hash: Hash,
+    rid: u64,
+    peers: SyncMap<Peer>,
+}
+
+impl PeerSyncState {
+    pub fn new(hash: Hash) -> Self {
+        Self {
+            hash,
+            rid: 0,
+            peers: SyncMap::new(None),
+        }
+    }
+
+    /// Request the changes since the last update and apply them.
+    pub async fn update(&mut self, api: &Api) -> Result<()> {
+        let data = self.hash.peers(api, self.rid).await?;
+        self.apply(data)
+    }
+
+    /// Apply a (possibly partial) peers response to the local state. If the
+    /// response fails to deserialize the state is unchanged and the rid is
+    /// reset, as in `SyncState::apply`.
+    pub fn apply(&mut self, data: TorrentPeers) -> Result<()> {
+        let peers = match self
+            .peers
+            .stage(data.full_update, data.peers, data.peers_removed)
+        {
+            Ok(peers) => peers,
+            Err(e) => {
+                self.rid = 0;
+                return Err(e);
+            }
+        };
+        self.peers.commit(peers);
+        self.rid = data.rid;
+        Ok(())
+    }
+
+    pub fn hash(&self) -> &Hash {
+        &self.hash
+    }
+
+    /// Response id of the last applied update.
+    pub fn rid(&self) -> u64 {
+        self.rid
+    }
+
+    /// Peers keyed by `ip:port`.
+    pub fn peers(&self) -> &HashMap<String, Peer> {
+        self.peers.items()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const DEBIAN: &str = "8c4adbf9ebe66f1d804fb6a4fb9b74966c3ab609";
+
+    fn full() -> MainData {
+        serde_json::from_str(include_str!(
+            "../tests/fixtures/4-stop-start/sync_maindata.json"
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn failed_update() {
+        let mut state = SyncState::new();
+        state.apply(full()).unwrap();
+        assert_eq!(state.rid(), 1);
+
+        // A failed update leaves the state as it was, and asks for a full
+        // update next.
+        let partial = json!({
+            "rid": 2,
+            "torrents": {
+                DEBIAN: {"name": "renamed"},
+                "e2467cbf021192c241367b892230dc1e05c0580e": {"progress": "invalid"},
+            },
+            "torrents_removed": ["2aa4f5a7e209e54b32803d43670971c4c8caaa05"],
+            "categories_removed": ["tv"],
+            "tags_removed": ["iso"],
+        });
+        let data: MainData = serde_json::from_value(partial).unwrap();
+        assert!(state.apply(data).is_err());
+        assert_eq!(state.rid(), 0);
+        assert_eq!(state.torrents().count(), 3);
+        assert_ne!(state.torrent(DEBIAN).unwrap().name(), "renamed");
+        assert_eq!(state.categories().len(), 2);
+        assert!(state.tags().contains("iso"));
+
+        let data: MainData =
+            serde_json::from_value(json!({"rid": 3, "server_state": {"global_ratio": []}}))
+                .unwrap();
+        state.apply(full()).unwrap();
+        assert_eq!(state.rid(), 1);
+        assert!(state.apply(data).is_err());
+        assert_eq!(state.rid(), 0);
+        assert_eq!(*state.server_state().global_ratio(), 1.34);
+    }
+
+    #[test]
+    fn failed_peers_update() {
+        let mut state = PeerSyncState::new(String::new().into());
+        let data = include_str!("../tests/fixtures/4-stop-start/sync_torrentPeers.json");
+        state.apply(serde_json::from_str(data).unwrap()).unwrap();
+        assert_ne!(state.rid(), 0);
+
+        let (addr, _) = state.peers().iter().next().unwrap();
+        let data = json!({"rid": 9, "peers": {addr.clone(): {"port": "invalid"}}});
+        assert!(state.apply(serde_json::from_value(data).unwrap()).is_err());
+        assert_eq!(state.rid(), 0);
+        assert_eq!(state.peers().len(), 2);
+    }
+}
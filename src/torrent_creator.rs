@@ -0,0 +1,193 @@
+//! Creating .torrent files with `/api/v2/torrentcreator/*`
+
+use crate::api::Api;
+use crate::data::ApiVersion;
+use crate::error::{Error, Result};
+use derive_builder::Builder;
+use derive_getters::Getters;
+use serde::{Deserialize, Serialize, Serializer};
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// First version with the torrent creator, qbittorrent 5.0.
+const TORRENT_CREATOR_VERSION: ApiVersion = ApiVersion::new(2, 11, 0);
+
+#[derive(Debug, Deserialize, Serialize, Copy, Clone, Eq, PartialEq)]
+pub enum TorrentFormat {
+    #[serde(rename = "v1")]
+    V1,
+    #[serde(rename = "v2")]
+    V2,
+    #[serde(rename = "hybrid")]
+    Hybrid,
+}
+
+/// Options for a new torrent creator task, `source_path` is a file or folder
+/// on the qbittorrent host.
+#[derive(Debug, Clone, Serialize, Builder)]
+#[serde(rename_all = "camelCase")]
+#[builder(setter(into, strip_option))]
+pub struct TorrentCreatorTask {
+    source_path: String,
+    /// Also save the .torrent file to this path on the qbittorrent host
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    torrent_file_path: Option<String>,
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    format: Option<TorrentFormat>,
+    /// Piece size in bytes, 0 picks one automatically
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    piece_size: Option<u64>,
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    private: Option<bool>,
+    /// Add the created torrent to qbittorrent and start seeding it
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    start_seeding: Option<bool>,
+    #[builder(default, setter(each(name = "tracker", into)))]
+    #[serde(
+        serialize_with = "serialize_joined",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    trackers: Vec<String>,
+    #[builder(default, setter(each(name = "url_seed", into)))]
+    #[serde(
+        serialize_with = "serialize_joined",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    url_seeds: Vec<String>,
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    comment: Option<String>,
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    source: Option<String>,
+}
+
+fn serialize_joined<S: Serializer>(
+    values: &[String],
+    serializer: S,
+) -> std::result::Result<S::Ok, S::Error> {
+    serializer.serialize_str(&values.join("|"))
+}
+
+#[derive(Debug, Deserialize, Serialize, Copy, Clone, Eq, PartialEq)]
+pub enum TaskStatus {
+    Queued,
+    Running,
+    Finished,
+    Failed,
+}
+
+#[derive(Debug, Deserialize, Clone, Getters)]
+#[serde(rename_all = "camelCase")]
+pub struct TorrentCreatorStatus {
+    #[serde(rename = "taskID")]
+    task_id: String,
+    source_path: String,
+    status: TaskStatus,
+    /// Progress in percent while running
+    progress: Option<f64>,
+    error_message: Option<String>,
+    piece_size: Option<u64>,
+    private: Option<bool>,
+    format: Option<TorrentFormat>,
+    time_added: Option<String>,
+    time_started: Option<String>,
+    time_finished: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct AddTask {
+    #[serde(rename = "taskID")]
+    task_id: String,
+}
+
+/// Handle to a torrent creator task.
+#[derive(Debug)]
+pub struct TorrentCreation<'a> {
+    api: &'a Api,
+    id: String,
+}
+
+impl TorrentCreation<'_> {
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub async fn status(&self) -> Result<TorrentCreatorStatus> {
+        let mut status = self.api.torrent_creator_status(Some(&self.id)).await?;
+        status.pop().ok_or(Error::BadResponse)
+    }
+
+    /// Poll the status every `interval` until the task has finished, then
+    /// return the created .torrent file.
+    pub async fn wait(&self, interval: Duration) -> Result<Vec<u8>> {
+        loop {
+            let status = self.status().await?;
+            match status.status {
+                TaskStatus::Finished => return self.api.torrent_creator_file(&self.id).await,
+                TaskStatus::Failed => {
+                    let message = status.error_message.unwrap_or_default();
+                    return Err(Error::TorrentCreationFailed(message));
+                }
+                TaskStatus::Queued | TaskStatus::Running => tokio::time::sleep(interval).await,
+            }
+        }
+    }
+
+    /// Remove the task, aborting it if it is still running.
+    pub async fn delete(self) -> Result<()> {
+        self.api.torrent_creator_delete(&self.id).await
+    }
+}
+
+impl Api {
+    pub async fn torrent_creator_add_task(
+        &self,
+        task: &TorrentCreatorTask,
+    ) -> Result<TorrentCreation<'_>> {
+        self.require(TORRENT_CREATOR_VERSION)?;
+        let added: AddTask = self
+            .post_decode("/api/v2/torrentcreator/addTask", task)
+            .await?;
+        Ok(TorrentCreation {
+            api: self,
+            id: added.task_id,
+        })
+    }
+
+    /// Status of the task `id`, or of all tasks if `None`.
+    pub async fn torrent_creator_status(
+        &self,
+        id: Option<&str>,
+    ) -> Result<Vec<TorrentCreatorStatus>> {
+        self.require(TORRENT_CREATOR_VERSION)?;
+        let mut form = HashMap::new();
+        if let Some(id) = id {
+            form.insert("taskID", id);
+        }
+        self.post_decode("/api/v2/torrentcreator/status", &form)
+            .await
+    }
+
+    /// The .torrent file created by the finished task `id`.
+    pub async fn torrent_creator_file(&self, id: &str) -> Result<Vec<u8>> {
+        self.require(TORRENT_CREATOR_VERSION)?;
+        let mut form = HashMap::new();
+        form.insert("taskID", id);
+        self.post_bytes("/api/v2/torrentcreator/torrentFile", &form)
+            .await
+    }
+
+    pub async fn torrent_creator_delete(&self, id: &str) -> Result<()> {
+        self.require(TORRENT_CREATOR_VERSION)?;
+        let mut form = HashMap::new();
+        form.insert("taskID", id);
+        self.post_status("/api/v2/torrentcreator/deleteTask", &form)
+            .await
+    }
+}
@@ -1,9 +1,17 @@
-use crate::api::Api;
+use crate::api::{Api, STOP_START_VERSION};
 use crate::data::*;
 use crate::error::Result;
 use async_trait::async_trait;
 use std::collections::HashMap;
 
+/// First version which can add, edit and remove web seeds, qbittorrent 5.1.
+/// 5.0 reports 2.11.2 and does not have the endpoints.
+const EDIT_WEBSEEDS_VERSION: ApiVersion = ApiVersion::new(2, 11, 3);
+
+/// First version with `/api/v2/torrents/renameFolder`, `renameFile` took a
+/// file id and name before.
+const RENAME_PATH_VERSION: ApiVersion = ApiVersion::new(2, 7, 0);
+
 #[async_trait]
 pub trait TorrentsApi {
     fn hashes(&self) -> String;
@@ -11,13 +19,23 @@ pub trait TorrentsApi {
     async fn stop(&self, api: &Api) -> Result<()> {
         let mut form = HashMap::new();
         form.insert("hashes", self.hashes());
-        api.post_status("/api/v2/torrents/stop", &form).await
+        let path = api.versioned(
+            STOP_START_VERSION,
+            "/api/v2/torrents/stop",
+            "/api/v2/torrents/pause",
+        );
+        api.post_status(path, &form).await
     }
 
     async fn start(&self, api: &Api) -> Result<()> {
         let mut form = HashMap::new();
         form.insert("hashes", self.hashes());
-        api.post_status("/api/v2/torrents/start", &form).await
+        let path = api.versioned(
+            STOP_START_VERSION,
+            "/api/v2/torrents/start",
+            "/api/v2/torrents/resume",
+        );
+        api.post_status(path, &form).await
     }
 
     async fn delete(&self, api: &Api, delete_data: bool) -> Result<()> {
@@ -68,6 +86,125 @@ pub trait TorrentsApi {
         form.insert("hashes", self.hashes());
         api.post_status("/api/v2/torrents/topPrio", &form).await
     }
+
+    async fn increase_priority(&self, api: &Api) -> Result<()> {
+        let mut form = HashMap::new();
+        form.insert("hashes", self.hashes());
+        api.post_status("/api/v2/torrents/increasePrio", &form)
+            .await
+    }
+
+    async fn decrease_priority(&self, api: &Api) -> Result<()> {
+        let mut form = HashMap::new();
+        form.insert("hashes", self.hashes());
+        api.post_status("/api/v2/torrents/decreasePrio", &form)
+            .await
+    }
+
+    async fn reannounce(&self, api: &Api) -> Result<()> {
+        let mut form = HashMap::new();
+        form.insert("hashes", self.hashes());
+        api.post_status("/api/v2/torrents/reannounce", &form).await
+    }
+
+    /// Download limit (bytes/s) keyed by torrent hash, 0 if unlimited.
+    async fn download_limit(&self, api: &Api) -> Result<HashMap<String, i64>> {
+        let mut form = HashMap::new();
+        form.insert("hashes", self.hashes());
+        api.post_decode("/api/v2/torrents/downloadLimit", &form)
+            .await
+    }
+
+    /// Set the download limit (bytes/s), 0 or -1 for unlimited.
+    async fn set_download_limit(&self, api: &Api, limit: i64) -> Result<()> {
+        let mut form = HashMap::new();
+        form.insert("hashes", self.hashes());
+        form.insert("limit", limit.to_string());
+        api.post_status("/api/v2/torrents/setDownloadLimit", &form)
+            .await
+    }
+
+    /// Upload limit (bytes/s) keyed by torrent hash, 0 if unlimited.
+    async fn upload_limit(&self, api: &Api) -> Result<HashMap<String, i64>> {
+        let mut form = HashMap::new();
+        form.insert("hashes", self.hashes());
+        api.post_decode("/api/v2/torrents/uploadLimit", &form).await
+    }
+
+    /// Set the upload limit (bytes/s), 0 or -1 for unlimited.
+    async fn set_upload_limit(&self, api: &Api, limit: i64) -> Result<()> {
+        let mut form = HashMap::new();
+        form.insert("hashes", self.hashes());
+        form.insert("limit", limit.to_string());
+        api.post_status("/api/v2/torrents/setUploadLimit", &form)
+            .await
+    }
+
+    /// Set the share limits, `-2` uses the global limit and `-1` means no
+    /// limit. The seeding time limits are in minutes.
+    async fn set_share_limits(
+        &self,
+        api: &Api,
+        ratio_limit: f64,
+        seeding_time_limit: i64,
+        inactive_seeding_time_limit: i64,
+    ) -> Result<()> {
+        let mut form = HashMap::new();
+        form.insert("hashes", self.hashes());
+        form.insert("ratioLimit", ratio_limit.to_string());
+        form.insert("seedingTimeLimit", seeding_time_limit.to_string());
+        form.insert(
+            "inactiveSeedingTimeLimit",
+            inactive_seeding_time_limit.to_string(),
+        );
+        api.post_status("/api/v2/torrents/setShareLimits", &form)
+            .await
+    }
+
+    async fn set_location(&self, api: &Api, location: &str) -> Result<()> {
+        let mut form = HashMap::new();
+        form.insert("hashes", self.hashes());
+        form.insert("location", location.into());
+        api.post_status("/api/v2/torrents/setLocation", &form).await
+    }
+
+    async fn set_auto_management(&self, api: &Api, enable: bool) -> Result<()> {
+        let mut form = HashMap::new();
+        form.insert("hashes", self.hashes());
+        form.insert("enable", enable.to_string());
+        api.post_status("/api/v2/torrents/setAutoManagement", &form)
+            .await
+    }
+
+    async fn toggle_sequential_download(&self, api: &Api) -> Result<()> {
+        let mut form = HashMap::new();
+        form.insert("hashes", self.hashes());
+        api.post_status("/api/v2/torrents/toggleSequentialDownload", &form)
+            .await
+    }
+
+    async fn toggle_first_last_piece_priority(&self, api: &Api) -> Result<()> {
+        let mut form = HashMap::new();
+        form.insert("hashes", self.hashes());
+        api.post_status("/api/v2/torrents/toggleFirstLastPiecePrio", &form)
+            .await
+    }
+
+    async fn set_force_start(&self, api: &Api, value: bool) -> Result<()> {
+        let mut form = HashMap::new();
+        form.insert("hashes", self.hashes());
+        form.insert("value", value.to_string());
+        api.post_status("/api/v2/torrents/setForceStart", &form)
+            .await
+    }
+
+    async fn set_super_seeding(&self, api: &Api, value: bool) -> Result<()> {
+        let mut form = HashMap::new();
+        form.insert("hashes", self.hashes());
+        form.insert("value", value.to_string());
+        api.post_status("/api/v2/torrents/setSuperSeeding", &form)
+            .await
+    }
 }
 
 impl TorrentsApi for Torrent {
@@ -126,11 +263,127 @@ pub trait TorrentApi {
         api.post_decode("/api/v2/torrents/trackers", &form).await
     }
 
+    async fn add_trackers(&self, api: &Api, urls: &[String]) -> Result<()> {
+        let mut form = HashMap::new();
+        form.insert("hash", self.hash());
+        form.insert("urls", urls.join("\n"));
+        api.post_status("/api/v2/torrents/addTrackers", &form).await
+    }
+
+    /// Replace the tracker `orig_url` with `new_url`.
+    async fn edit_tracker(&self, api: &Api, orig_url: &str, new_url: &str) -> Result<()> {
+        let mut form = HashMap::new();
+        form.insert("hash", self.hash());
+        form.insert("origUrl", orig_url.into());
+        form.insert("newUrl", new_url.into());
+        api.post_status("/api/v2/torrents/editTracker", &form).await
+    }
+
+    async fn remove_trackers(&self, api: &Api, urls: &[String]) -> Result<()> {
+        let mut form = HashMap::new();
+        form.insert("hash", self.hash());
+        form.insert("urls", urls.join("|"));
+        api.post_status("/api/v2/torrents/removeTrackers", &form)
+            .await
+    }
+
+    async fn webseeds(&self, api: &Api) -> Result<Vec<WebSeed>> {
+        let mut form = HashMap::new();
+        form.insert("hash", self.hash());
+        api.post_decode("/api/v2/torrents/webseeds", &form).await
+    }
+
+    async fn add_webseeds(&self, api: &Api, urls: &[String]) -> Result<()> {
+        api.require(EDIT_WEBSEEDS_VERSION)?;
+        let mut form = HashMap::new();
+        form.insert("hash", self.hash());
+        form.insert("urls", urls.join("|"));
+        api.post_status("/api/v2/torrents/addWebSeeds", &form).await
+    }
+
+    /// Replace the web seed `orig_url` with `new_url`.
+    async fn edit_webseed(&self, api: &Api, orig_url: &str, new_url: &str) -> Result<()> {
+        api.require(EDIT_WEBSEEDS_VERSION)?;
+        let mut form = HashMap::new();
+        form.insert("hash", self.hash());
+        form.insert("origUrl", orig_url.into());
+        form.insert("newUrl", new_url.into());
+        api.post_status("/api/v2/torrents/editWebSeed", &form).await
+    }
+
+    async fn remove_webseeds(&self, api: &Api, urls: &[String]) -> Result<()> {
+        api.require(EDIT_WEBSEEDS_VERSION)?;
+        let mut form = HashMap::new();
+        form.insert("hash", self.hash());
+        form.insert("urls", urls.join("|"));
+        api.post_status("/api/v2/torrents/removeWebSeeds", &form)
+            .await
+    }
+
     async fn contents(&self, api: &Api) -> Result<Vec<TorrentInfo>> {
         let mut form = HashMap::new();
         form.insert("hash", self.hash());
         api.post_decode("/api/v2/torrents/files", &form).await
     }
+
+    async fn piece_states(&self, api: &Api) -> Result<PieceMap> {
+        let mut form = HashMap::new();
+        form.insert("hash", self.hash());
+        api.post_decode("/api/v2/torrents/pieceStates", &form).await
+    }
+
+    async fn piece_hashes(&self, api: &Api) -> Result<Vec<PieceHash>> {
+        let mut form = HashMap::new();
+        form.insert("hash", self.hash());
+        api.post_decode("/api/v2/torrents/pieceHashes", &form).await
+    }
+
+    /// Set the priority of the files with the ids `file_ids`, see
+    /// `TorrentInfo::index`.
+    async fn set_file_priority(
+        &self,
+        api: &Api,
+        file_ids: &[u64],
+        priority: FilePriority,
+    ) -> Result<()> {
+        let ids: Vec<String> = file_ids.iter().map(|id| id.to_string()).collect();
+        let mut form = HashMap::new();
+        form.insert("hash", self.hash());
+        form.insert("id", ids.join("|"));
+        form.insert("priority", i64::from(priority).to_string());
+        api.post_status("/api/v2/torrents/filePrio", &form).await
+    }
+
+    /// Rename the file at `old_path`, paths are relative to the torrent's root.
+    async fn rename_file(&self, api: &Api, old_path: &str, new_path: &str) -> Result<()> {
+        api.require(RENAME_PATH_VERSION)?;
+        let mut form = HashMap::new();
+        form.insert("hash", self.hash());
+        form.insert("oldPath", old_path.into());
+        form.insert("newPath", new_path.into());
+        api.post_status("/api/v2/torrents/renameFile", &form).await
+    }
+
+    /// Rename the folder at `old_path`, paths are relative to the torrent's
+    /// root.
+    async fn rename_folder(&self, api: &Api, old_path: &str, new_path: &str) -> Result<()> {
+        api.require(RENAME_PATH_VERSION)?;
+        let mut form = HashMap::new();
+        form.insert("hash", self.hash());
+        form.insert("oldPath", old_path.into());
+        form.insert("newPath", new_path.into());
+        api.post_status("/api/v2/torrents/renameFolder", &form)
+            .await
+    }
+
+    /// Changes to the peer list since response id `rid`, use `0` to request a
+    /// full update.
+    async fn peers(&self, api: &Api, rid: u64) -> Result<TorrentPeers> {
+        let mut form = HashMap::new();
+        form.insert("hash", self.hash());
+        form.insert("rid", rid.to_string());
+        api.post_decode("/api/v2/sync/torrentPeers", &form).await
+    }
 }
 
 impl TorrentApi for Torrent {
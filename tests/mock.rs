@@ -0,0 +1,669 @@
+use futures::StreamExt;
+use qb_api::data::{AlternateLimits, ApiVersion, LogLevel, State};
+use qb_api::events::{TorrentEvent, TorrentWatcher};
+use qb_api::metainfo::Metainfo;
+use qb_api::mock::{MockServer, MockTorrent, MAX_LOGIN_FAILURES};
+use qb_api::queries::*;
+use qb_api::rss::AutoDownloadRuleBuilder;
+use qb_api::search::{SearchResult, SearchStatus};
+use qb_api::sync::SyncState;
+use qb_api::torrent_creator::{TaskStatus, TorrentCreatorTaskBuilder};
+use qb_api::traits::*;
+use qb_api::{Api, Error};
+use std::time::Duration;
+
+const HASH_A: &str = "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa";
+const HASH_B: &str = "bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb";
+const HASH_C: &str = "cccccccccccccccccccccccccccccccccccccccc";
+
+async fn setup() -> (MockServer, Api) {
+    let server = MockServer::start().await.unwrap();
+
+    let mut a = MockTorrent::new(HASH_A, "alpha");
+    a.size = 100;
+    a.dlspeed = 10;
+    let mut b = MockTorrent::new(HASH_B, "bravo");
+    b.size = 300;
+    b.progress = 1.0;
+    b.state = "uploading".into();
+    let mut c = MockTorrent::new(HASH_C, "charlie");
+    c.size = 200;
+    c.state = "stoppedDL".into();
+    server.add_torrent(a);
+    server.add_torrent(b);
+    server.add_torrent(c);
+
+    let api = Api::auth(&server.url(), "admin", "adminadmin")
+        .await
+        .unwrap();
+    (server, api)
+}
+
+/// A single file v1 .torrent
+fn torrent_file(name: &str) -> Vec<u8> {
+    let mut info = Vec::new();
+    info.extend_from_slice(b"d6:lengthi5e");
+    info.extend_from_slice(format!("4:name{}:{}", name.len(), name).as_bytes());
+    info.extend_from_slice(b"12:piece lengthi16384e6:pieces20:");
+    info.extend_from_slice(&[0u8; 20]);
+    info.push(b'e');
+
+    let mut data = b"d8:announce20:http://tracker/annce4:info".to_vec();
+    data.extend_from_slice(&info);
+    data.push(b'e');
+    data
+}
+
+fn names(torrents: &[qb_api::data::Torrent]) -> Vec<&str> {
+    torrents.iter().map(|t| t.name().as_str()).collect()
+}
+
+#[tokio::test]
+async fn login() {
+    let server = MockServer::start_with_credentials("user", "secret")
+        .await
+        .unwrap();
+
+    let err = Api::auth(&server.url(), "user", "wrong").await.unwrap_err();
+    assert!(matches!(err, Error::MissingCookie));
+    let err = Api::local(&server.url()).await.unwrap_err();
+    assert!(matches!(err, Error::MissingCookie));
+
+    let api = Api::auth(&server.url(), "user", "secret").await.unwrap();
+    assert_eq!(api.get_api_version().await.unwrap(), "2.11.2");
+}
+
+#[tokio::test]
+async fn login_banned() {
+    let server = MockServer::start_with_credentials("user", "secret")
+        .await
+        .unwrap();
+    for _ in 0..MAX_LOGIN_FAILURES {
+        let err = Api::auth(&server.url(), "user", "wrong").await.unwrap_err();
+        assert!(matches!(err, Error::MissingCookie));
+    }
+
+    let err = Api::auth(&server.url(), "user", "secret")
+        .await
+        .unwrap_err();
+    assert!(matches!(err, Error::Forbidden { .. }), "{}", err);
+}
+
+#[tokio::test]
+async fn local_bypass() {
+    let server = MockServer::start().await.unwrap();
+    let api = Api::local(&server.url()).await.unwrap();
+    assert_eq!(api.get_default_save_path().await.unwrap(), "/downloads");
+}
+
+#[tokio::test]
+async fn renew_expired_session() {
+    let (server, api) = setup().await;
+    server.expire_sessions();
+    assert_eq!(api.get_torrents().await.unwrap().len(), 3);
+}
+
+#[tokio::test]
+async fn filter_torrents() {
+    let (server, api) = setup().await;
+    server.update_torrent(HASH_A, |t| {
+        t.category = "linux".into();
+        t.tags.insert("iso".into());
+    });
+
+    let request = TorrentRequestBuilder::default()
+        .filter(TorrentFilter::Downloading)
+        .build()
+        .unwrap();
+    let torrents = api.get_torrents_filtered(&request).await.unwrap();
+    assert_eq!(names(&torrents), ["alpha"]);
+
+    let request = TorrentRequestBuilder::default()
+        .filter(TorrentFilter::Completed)
+        .build()
+        .unwrap();
+    let torrents = api.get_torrents_filtered(&request).await.unwrap();
+    assert_eq!(names(&torrents), ["bravo"]);
+
+    let request = TorrentRequestBuilder::default()
+        .category("linux")
+        .tag("iso")
+        .build()
+        .unwrap();
+    let torrents = api.get_torrents_filtered(&request).await.unwrap();
+    assert_eq!(names(&torrents), ["alpha"]);
+
+    let request = TorrentRequestBuilder::default()
+        .sort(TorrentSort::Size)
+        .reverse(true)
+        .limit(2u64)
+        .offset(1i64)
+        .build()
+        .unwrap();
+    let torrents = api.get_torrents_filtered(&request).await.unwrap();
+    assert_eq!(names(&torrents), ["charlie", "alpha"]);
+
+    // Hashes are matched regardless of case.
+    let hash_d = "dddddddddddddddddddddddddddddddddddddddd";
+    server.add_torrent(MockTorrent::new(hash_d.to_uppercase(), "delta"));
+    let request = TorrentRequestBuilder::default()
+        .hashes(vec![
+            hash_d.to_string().into(),
+            HASH_B.to_uppercase().into(),
+        ])
+        .build()
+        .unwrap();
+    let torrents = api.get_torrents_filtered(&request).await.unwrap();
+    assert_eq!(names(&torrents), ["bravo", "delta"]);
+}
+
+#[tokio::test]
+async fn stop_and_start() {
+    let (_server, api) = setup().await;
+    let torrents = api.get_torrents().await.unwrap();
+    torrents.stop(&api).await.unwrap();
+
+    let torrents = api.get_torrents().await.unwrap();
+    let stopped = torrents
+        .iter()
+        .filter(|t| matches!(t.state(), State::StoppedDL | State::StoppedUP))
+        .count();
+    assert_eq!(stopped, 3);
+
+    torrents.start(&api).await.unwrap();
+    torrents[..1].delete(&api, false).await.unwrap();
+    let torrents = api.get_torrents().await.unwrap();
+    assert_eq!(torrents.len(), 2);
+    assert!(torrents
+        .iter()
+        .all(|t| matches!(t.state(), State::Downloading | State::Uploading)));
+}
+
+#[tokio::test]
+async fn older_api_version() {
+    let server = MockServer::start().await.unwrap();
+    server.set_api_version(ApiVersion::new(2, 8, 5));
+    let mut torrent = MockTorrent::new(HASH_A, "alpha");
+    torrent.state = "stoppedDL".into();
+    server.add_torrent(torrent);
+    server.add_torrent(MockTorrent::new(HASH_B, "bravo"));
+
+    let api = Api::local(&server.url()).await.unwrap();
+    assert_eq!(api.api_version(), ApiVersion::new(2, 8, 5));
+
+    // Reported as pausedDL, filtered as paused.
+    let request = TorrentRequestBuilder::default()
+        .filter(TorrentFilter::Stopped)
+        .build()
+        .unwrap();
+    let torrents = api.get_torrents_filtered(&request).await.unwrap();
+    assert_eq!(names(&torrents), ["alpha"]);
+    assert_eq!(*torrents[0].state(), State::StoppedDL);
+
+    // Sent to torrents/pause and torrents/resume.
+    let torrents = api.get_torrents().await.unwrap();
+    torrents.stop(&api).await.unwrap();
+    assert!(server.torrents().iter().all(|t| t.state == "stoppedDL"));
+    torrents.start(&api).await.unwrap();
+    assert!(server.torrents().iter().all(|t| t.state == "downloading"));
+
+    let task = TorrentCreatorTaskBuilder::default()
+        .source_path("/downloads")
+        .build()
+        .unwrap();
+    let err = api.torrent_creator_add_task(&task).await.unwrap_err();
+    match err {
+        Error::Unsupported { needs, have } => {
+            assert_eq!(needs, ApiVersion::new(2, 11, 0));
+            assert_eq!(have, ApiVersion::new(2, 8, 5));
+        }
+        err => panic!("unexpected error: {}", err),
+    }
+    let err = torrents[0]
+        .add_webseeds(&api, &["http://example.org/alpha".into()])
+        .await
+        .unwrap_err();
+    assert!(matches!(err, Error::Unsupported { .. }));
+
+    // Sent as paused.
+    let add = AddTorrentBuilder::default()
+        .urls(format!("magnet:?xt=urn:btih:{}", HASH_C))
+        .paused("true")
+        .build()
+        .unwrap();
+    api.add_torrent(&add).await.unwrap();
+    let added = server.torrents();
+    assert_eq!(
+        added.iter().find(|t| t.hash == HASH_C).unwrap().state,
+        "stoppedDL"
+    );
+
+    // Files were renamed by id before 2.7.
+    server.set_api_version(ApiVersion::new(2, 6, 0));
+    let api = Api::local(&server.url()).await.unwrap();
+    let err = torrents[0]
+        .rename_file(&api, "alpha.iso", "beta.iso")
+        .await
+        .unwrap_err();
+    assert!(matches!(err, Error::Unsupported { .. }));
+}
+
+#[tokio::test]
+async fn webseeds_version() {
+    let (_server, api) = setup().await;
+    let torrents = api.get_torrents().await.unwrap();
+    let urls = ["http://example.org/alpha".to_string()];
+
+    // 5.0 reports 2.11.2 and cannot edit web seeds.
+    assert_eq!(api.get_api_version().await.unwrap(), "2.11.2");
+    for err in [
+        torrents[0].add_webseeds(&api, &urls).await.unwrap_err(),
+        torrents[0]
+            .edit_webseed(&api, &urls[0], "http://example.org/beta")
+            .await
+            .unwrap_err(),
+        torrents[0].remove_webseeds(&api, &urls).await.unwrap_err(),
+    ] {
+        match err {
+            Error::Unsupported { needs, have } => {
+                assert_eq!(needs, ApiVersion::new(2, 11, 3));
+                assert_eq!(have, ApiVersion::new(2, 11, 2));
+            }
+            err => panic!("unexpected error: {}", err),
+        }
+    }
+}
+
+#[tokio::test]
+async fn categories() {
+    let (server, api) = setup().await;
+    api.add_category("linux", "/linux").await.unwrap();
+
+    let err = api.add_category("linux", "/other").await.unwrap_err();
+    assert!(matches!(err, Error::CategoryExists { .. }));
+    let err = api.add_category("", "/other").await.unwrap_err();
+    assert!(matches!(err, Error::InvalidCategoryName { .. }));
+    let err = api.edit_category("missing", "/other").await.unwrap_err();
+    assert!(matches!(err, Error::CategoryNotFound { .. }));
+
+    api.edit_category("linux", "/iso").await.unwrap();
+    let categories = api.get_categories().await.unwrap();
+    assert_eq!(categories["linux"].save_path(), "/iso");
+
+    let torrents = api.get_torrents().await.unwrap();
+    torrents.set_category(&api, "linux").await.unwrap();
+    let err = torrents.set_category(&api, "missing").await.unwrap_err();
+    assert!(matches!(err, Error::CategoryNotFound { .. }));
+
+    api.remove_category("linux").await.unwrap();
+    assert!(api.get_categories().await.unwrap().is_empty());
+    assert!(server.torrents().iter().all(|t| t.category.is_empty()));
+}
+
+#[tokio::test]
+async fn tags() {
+    let (server, api) = setup().await;
+    api.create_tags(["one", "two"]).await.unwrap();
+
+    let torrents = api.get_torrents().await.unwrap();
+    torrents[..1]
+        .add_tags(&api, &["two".into(), "three".into()])
+        .await
+        .unwrap();
+    assert_eq!(api.get_tags().await.unwrap().len(), 3);
+
+    api.delete_tags(["two"]).await.unwrap();
+    let tags = api.get_tags().await.unwrap();
+    assert!(tags.contains("one") && tags.contains("three"));
+    assert_eq!(server.torrents()[0].tags.len(), 1);
+}
+
+#[tokio::test]
+async fn add_magnet() {
+    let (server, api) = setup().await;
+    let magnet = "magnet:?xt=urn:btih:dddddddddddddddddddddddddddddddddddddddd&dn=delta&xl=42";
+    let add = AddTorrentBuilder::default()
+        .urls(magnet)
+        .category("linux")
+        .build()
+        .unwrap();
+    let hashes = api.add_torrent(&add).await.unwrap();
+    assert_eq!(hashes.len(), 1);
+
+    let added = server.torrents();
+    let added = added.iter().find(|t| t.hash == **hashes[0]).unwrap();
+    assert_eq!(added.name, "delta");
+    assert_eq!(added.size, 42);
+    assert_eq!(added.category, "linux");
+
+    // Adding the same torrent again fails.
+    let err = api.add_torrent(&add).await.unwrap_err();
+    assert!(matches!(err, Error::AddTorrentFailed));
+
+    // Sent as stopped.
+    let magnet = "magnet:?xt=urn:btih:eeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeee";
+    let add = AddTorrentBuilder::default()
+        .urls(magnet)
+        .paused("true")
+        .build()
+        .unwrap();
+    let hashes = api.add_torrent(&add).await.unwrap();
+    let added = server.torrents();
+    let added = added.iter().find(|t| t.hash == **hashes[0]).unwrap();
+    assert_eq!(added.state, "stoppedDL");
+}
+
+#[tokio::test]
+async fn add_torrent_file() {
+    let (server, api) = setup().await;
+    let add = AddTorrentBuilder::default()
+        .torrent(TorrentFile::new("echo.torrent", torrent_file("echo")))
+        .paused("true")
+        .build()
+        .unwrap();
+    let hashes = api
+        .add_torrent_and_wait(&add, Duration::from_secs(5))
+        .await
+        .unwrap();
+    assert_eq!(hashes.len(), 1);
+
+    let torrents = api.get_torrents().await.unwrap();
+    let added = torrents.iter().find(|t| t.name() == "echo").unwrap();
+    assert_eq!(TorrentApi::hash(added), *hashes[0]);
+    assert_eq!(*added.size(), 5);
+    assert_eq!(*added.state(), State::StoppedDL);
+    assert_eq!(server.torrents().len(), 4);
+
+    // Files are parsed before they are uploaded.
+    let dir = std::env::temp_dir().join(format!("qb-api-add-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("foxtrot.torrent"), torrent_file("foxtrot")).unwrap();
+    std::fs::write(dir.join("invalid.torrent"), b"not a torrent").unwrap();
+    let hash = api
+        .add_torrent_file(dir.join("foxtrot.torrent"))
+        .await
+        .unwrap();
+    let invalid = api.add_torrent_file(dir.join("invalid.torrent")).await;
+    std::fs::remove_dir_all(&dir).unwrap();
+    assert!(matches!(invalid, Err(Error::InvalidTorrent(_))));
+    assert_eq!(server.torrents().len(), 5);
+    assert!(server.torrents().iter().any(|t| t.hash == *hash));
+}
+
+#[tokio::test]
+async fn transfer_limits() {
+    let (_server, api) = setup().await;
+    api.set_download_limit(1024).await.unwrap();
+    api.set_upload_limit(2048).await.unwrap();
+    assert_eq!(api.get_download_limit().await.unwrap(), 1024);
+    assert_eq!(api.get_upload_limit().await.unwrap(), 2048);
+
+    let info = api.get_global_transfer_info().await.unwrap();
+    assert_eq!(*info.dl_info_speed(), 10);
+    assert_eq!(*info.dl_rate_limit(), 1024);
+
+    for _ in 0..2 {
+        api.set_alt_speed_limits(AlternateLimits::Enabled)
+            .await
+            .unwrap();
+        assert_eq!(
+            api.get_alt_speed_limits_state().await.unwrap(),
+            AlternateLimits::Enabled
+        );
+    }
+    api.toggle_alt_speed_limits().await.unwrap();
+    assert_eq!(
+        api.get_alt_speed_limits_state().await.unwrap(),
+        AlternateLimits::Disabled
+    );
+}
+
+#[tokio::test]
+async fn logs() {
+    let (server, api) = setup().await;
+    server.push_log(LogLevel::Info, "first");
+    server.push_log(LogLevel::Warning, "second");
+    server.push_log(LogLevel::Critical, "third");
+
+    let request = LogRequestBuilder::default()
+        .warning(true)
+        .critical(true)
+        .build()
+        .unwrap();
+    let logs = api.get_main_logs(&request).await.unwrap();
+    let messages: Vec<&str> = logs.iter().map(|l| l.message().as_str()).collect();
+    assert_eq!(messages, ["second", "third"]);
+    assert_eq!(*logs[1].level(), LogLevel::Critical);
+
+    let request = LogRequestBuilder::default()
+        .info(true)
+        .warning(true)
+        .critical(true)
+        .last_known_id(0)
+        .build()
+        .unwrap();
+    let logs = api.get_main_logs(&request).await.unwrap();
+    let messages: Vec<&str> = logs.iter().map(|l| l.message().as_str()).collect();
+    assert_eq!(messages, ["second", "third"]);
+
+    let request = LogRequestBuilder::default()
+        .info(true)
+        .warning(true)
+        .critical(true)
+        .build()
+        .unwrap();
+    let tail = api.tail_logs(request, Duration::from_millis(10));
+    futures::pin_mut!(tail);
+    for expected in ["first", "second", "third"] {
+        assert_eq!(tail.next().await.unwrap().unwrap().message(), expected);
+    }
+    server.push_log(LogLevel::Info, "fourth");
+    assert_eq!(tail.next().await.unwrap().unwrap().message(), "fourth");
+
+    api.ban_peers(&["10.0.0.1:6881".parse().unwrap()])
+        .await
+        .unwrap();
+    let peers = api.get_peer_logs(None).await.unwrap();
+    assert_eq!(peers.len(), 1);
+    assert_eq!(peers[0].ip(), "10.0.0.1");
+    assert!(*peers[0].blocked());
+}
+
+#[tokio::test]
+async fn sync_and_events() {
+    let (server, api) = setup().await;
+    let mut state = SyncState::new();
+    state.update(&api).await.unwrap();
+    assert_eq!(state.torrents().count(), 3);
+    assert_eq!(state.torrent(HASH_B).unwrap().name(), "bravo");
+
+    // Later updates only hold the changes since the requested rid.
+    server.update_torrent(HASH_B, |t| t.name = "bravo 2".into());
+    server.remove_torrent(HASH_C);
+    let data = api.sync_maindata(state.rid()).await.unwrap();
+    assert!(!data.full_update());
+    assert_eq!(data.torrents().len(), 1);
+    assert_eq!(data.torrents()[HASH_B].len(), 1);
+    assert_eq!(data.torrents_removed(), &[HASH_C]);
+    assert!(data.categories().is_empty() && data.tags().is_empty());
+    state.apply(data).unwrap();
+    assert_eq!(state.torrents().count(), 2);
+    assert_eq!(state.torrent(HASH_B).unwrap().name(), "bravo 2");
+
+    // Unknown rids get a full update.
+    let data = api.sync_maindata(12345).await.unwrap();
+    assert!(data.full_update());
+    assert_eq!(data.torrents().len(), 2);
+    server.add_torrent(MockTorrent::new(HASH_C, "charlie"));
+
+    let mut watcher = TorrentWatcher::new();
+    assert!(watcher.poll(&api).await.unwrap().is_empty());
+
+    server.update_torrent(HASH_A, |t| {
+        t.progress = 1.0;
+        t.state = "uploading".into();
+    });
+    server.remove_torrent(HASH_C);
+    let events = watcher.poll(&api).await.unwrap();
+    assert!(events
+        .iter()
+        .any(|e| matches!(e, TorrentEvent::Finished(t) if t.name() == "alpha")));
+    assert!(events.iter().any(|e| matches!(
+        e,
+        TorrentEvent::StateChanged {
+            from: State::Downloading,
+            ..
+        }
+    )));
+    assert!(events
+        .iter()
+        .any(|e| matches!(e, TorrentEvent::Removed(t) if t.name() == "charlie")));
+}
+
+#[tokio::test]
+async fn rss_feeds() {
+    let (_server, api) = setup().await;
+    let url = "https://example.org/releases.rss";
+    api.rss_add_folder("Linux").await.unwrap();
+    api.rss_add_feed(url, "Linux\\Releases").await.unwrap();
+
+    let err = api.rss_add_feed(url, "Linux\\Releases").await.unwrap_err();
+    assert!(matches!(err, Error::Conflict { .. }));
+    let err = api
+        .rss_add_feed(url, "Missing\\Releases")
+        .await
+        .unwrap_err();
+    assert!(matches!(err, Error::Conflict { .. }));
+
+    let items = api.rss_items(true).await.unwrap();
+    let feeds = items.feeds();
+    assert_eq!(feeds.len(), 1);
+    assert_eq!(feeds[0].0, "Linux\\Releases");
+    assert_eq!(feeds[0].1.url(), url);
+    assert!(feeds[0].1.articles().is_empty());
+
+    api.rss_move_item("Linux\\Releases", "Releases")
+        .await
+        .unwrap();
+    let items = api.rss_items(false).await.unwrap();
+    assert_eq!(items.feeds()[0].0, "Releases");
+
+    api.rss_remove_item("Releases").await.unwrap();
+    api.rss_remove_item("Linux").await.unwrap();
+    assert!(api.rss_items(false).await.unwrap().feeds().is_empty());
+    let err = api.rss_remove_item("Linux").await.unwrap_err();
+    assert!(matches!(err, Error::Conflict { .. }));
+}
+
+#[tokio::test]
+async fn rss_rules() {
+    let (_server, api) = setup().await;
+    let rule = AutoDownloadRuleBuilder::default()
+        .must_contain("debian")
+        .use_regex(true)
+        .affected_feeds(vec!["https://example.org/releases.rss".to_string()])
+        .add_paused(true)
+        .assigned_category("linux")
+        .build()
+        .unwrap();
+    api.rss_set_rule("debian", &rule).await.unwrap();
+
+    let rules = api.rss_rules().await.unwrap();
+    let stored = &rules["debian"];
+    assert_eq!(stored.must_contain(), "debian");
+    assert!(*stored.use_regex());
+    assert_eq!(stored.affected_feeds(), rule.affected_feeds());
+    assert_eq!(*stored.add_paused(), Some(true));
+    assert_eq!(stored.assigned_category(), "linux");
+    assert!(*stored.enabled());
+
+    api.rss_rename_rule("debian", "debian stable")
+        .await
+        .unwrap();
+    let rules = api.rss_rules().await.unwrap();
+    assert_eq!(rules.keys().collect::<Vec<_>>(), ["debian stable"]);
+    let articles = api.rss_matching_articles("debian stable").await.unwrap();
+    assert!(articles.is_empty());
+
+    api.rss_remove_rule("debian stable").await.unwrap();
+    assert!(api.rss_rules().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn search_job() {
+    let (server, api) = setup().await;
+    let interval = Duration::from_millis(10);
+    let file_names = |results: Option<Vec<SearchResult>>| -> Vec<String> {
+        results
+            .unwrap()
+            .iter()
+            .map(|r| r.file_name().clone())
+            .collect()
+    };
+
+    let mut job = api.search_start("debian", "all", "all").await.unwrap();
+    let id = job.id();
+    assert!(server.push_search_results(id, &["one", "two"]));
+    let page = job.next_results(interval).await.unwrap();
+    assert_eq!(file_names(page), ["one", "two"]);
+
+    // A running job without new results is polled until some arrive.
+    let (page, _) = tokio::join!(job.next_results(interval), async {
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        server.push_search_results(id, &["three"]);
+    });
+    assert_eq!(file_names(page.unwrap()), ["three"]);
+
+    server.push_search_results(id, &["four"]);
+    job.stop().await.unwrap();
+    let status = job.status().await.unwrap();
+    assert_eq!(*status.status(), SearchStatus::Stopped);
+    assert_eq!(*status.total(), 4);
+
+    // The results found before stopping are returned, then nothing.
+    let page = job.next_results(interval).await.unwrap();
+    assert_eq!(file_names(page), ["four"]);
+    assert!(job.next_results(interval).await.unwrap().is_none());
+
+    job.delete().await.unwrap();
+    let err = api.search_status(Some(id)).await.unwrap_err();
+    assert!(matches!(err, Error::SearchJobNotFound { .. }));
+}
+
+#[tokio::test]
+async fn torrent_creator() {
+    let (_server, api) = setup().await;
+    let interval = Duration::from_millis(10);
+
+    let task = TorrentCreatorTaskBuilder::default()
+        .source_path("/downloads/share")
+        .private(true)
+        .build()
+        .unwrap();
+    let creation = api.torrent_creator_add_task(&task).await.unwrap();
+    let data = creation.wait(interval).await.unwrap();
+    let metainfo = Metainfo::from_bytes(&data).unwrap();
+    assert_eq!(metainfo.name(), "share");
+    assert!(*metainfo.private());
+    assert_eq!(
+        *creation.status().await.unwrap().status(),
+        TaskStatus::Finished
+    );
+
+    let id = creation.id().to_string();
+    creation.delete().await.unwrap();
+    let err = api.torrent_creator_status(Some(&id)).await.unwrap_err();
+    assert!(matches!(err, Error::TaskNotFound { .. }));
+
+    let task = TorrentCreatorTaskBuilder::default()
+        .source_path("/missing")
+        .build()
+        .unwrap();
+    let creation = api.torrent_creator_add_task(&task).await.unwrap();
+    match creation.wait(interval).await {
+        Err(Error::TorrentCreationFailed(message)) => assert!(message.contains("no files")),
+        other => panic!("unexpected result: {:?}", other),
+    }
+    let err = api.torrent_creator_file(creation.id()).await.unwrap_err();
+    assert!(matches!(err, Error::Conflict { .. }));
+}